@@ -0,0 +1,94 @@
+use std::{fs, time::Duration};
+
+use interpreter_starter_rust::{FakeClock, OptLevel, Options};
+
+#[derive(Debug, Default)]
+pub struct Args {
+    pub command: String,
+    pub filename: String,
+    pub options: Options,
+}
+
+fn option(args: &mut Args, name: &str, value: &str) -> Result<(), String> {
+    match name {
+        "--allow-fs" => {
+            let root = fs::canonicalize(value)
+                .map_err(|error| format!("Invalid --allow-fs directory '{value}': {error}"))?;
+            args.options.fs_roots.push(root);
+        }
+        "--seed" => {
+            let seed = value
+                .parse()
+                .map_err(|_| format!("Invalid --seed value '{value}'"))?;
+            args.options.seed = Some(seed);
+        }
+        "--fake-clock" => {
+            let invalid = || format!("Invalid --fake-clock value '{value}', expected START[:STEP]");
+            let (start, step) = value.split_once(':').unwrap_or((value, "0"));
+
+            args.options.fake_clock = Some(FakeClock {
+                start: start.parse().map_err(|_| invalid())?,
+                step: step.parse().map_err(|_| invalid())?,
+            });
+        }
+        "--max-steps" => {
+            let steps = value
+                .parse()
+                .map_err(|_| format!("Invalid --max-steps value '{value}'"))?;
+            args.options.max_steps = Some(steps);
+        }
+        "--max-depth" => {
+            let depth = value
+                .parse()
+                .map_err(|_| format!("Invalid --max-depth value '{value}'"))?;
+            args.options.max_depth = Some(depth);
+        }
+        "--timeout" => {
+            let millis = value
+                .parse()
+                .map_err(|_| format!("Invalid --timeout value '{value}', expected milliseconds"))?;
+            args.options.timeout = Some(Duration::from_millis(millis));
+        }
+        "--max-memory" => {
+            let bytes = value
+                .parse()
+                .map_err(|_| format!("Invalid --max-memory value '{value}', expected bytes"))?;
+            args.options.max_memory = Some(bytes);
+        }
+        _ => return Err(format!("Unknown option: {name}")),
+    }
+
+    Ok(())
+}
+
+// Options go between the command and the filename, anything after the
+// filename is passed through to the script:
+// `<command> [--allow-fs=DIR]... [--seed=N] [--fake-clock=START[:STEP]] [--max-steps=N]
+// [--max-depth=N] [--timeout=MS] [--max-memory=BYTES] [-O0|-O1] <filename> [args]...`
+pub fn parse(argv: &[String]) -> Result<Args, String> {
+    let mut args = Args::default();
+    let mut argv = argv.iter().skip(1);
+
+    args.command = argv.next().cloned().ok_or("Missing command")?;
+
+    for arg in argv {
+        if !args.filename.is_empty() {
+            args.options.script_args.push(arg.clone());
+        } else if let Some(flag) = arg.strip_prefix("--") {
+            let (name, value) = flag.split_once('=').unwrap_or((flag, ""));
+            option(&mut args, &format!("--{name}"), value)?;
+        } else if arg == "-O0" {
+            args.options.opt_level = OptLevel::O0;
+        } else if arg == "-O1" {
+            args.options.opt_level = OptLevel::O1;
+        } else {
+            args.filename.clone_from(arg);
+        }
+    }
+
+    if args.filename.is_empty() {
+        return Err(String::from("Missing filename"));
+    }
+
+    Ok(args)
+}
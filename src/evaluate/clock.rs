@@ -0,0 +1,65 @@
+use std::{
+    cell::Cell,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+// Replaces the system clock with one that starts at `start` seconds and
+// advances by `step` seconds on every reading, so output is reproducible.
+// Time since the interpreter started counts from 0 at the same step, with
+// its own readings so calling one clock doesn't move the other.
+#[derive(Clone, Copy, Debug)]
+pub struct FakeClock {
+    pub start: f64,
+    pub step: f64,
+}
+
+#[derive(Debug)]
+pub struct Clock {
+    started: Instant,
+    fake: Option<FakeClock>,
+    now_readings: Cell<u32>,
+    elapsed_readings: Cell<u32>,
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl Clock {
+    pub fn new(fake: Option<FakeClock>) -> Self {
+        Self {
+            started: Instant::now(),
+            fake,
+            now_readings: Cell::new(0),
+            elapsed_readings: Cell::new(0),
+        }
+    }
+
+    fn fake_reading(origin: f64, step: f64, readings: &Cell<u32>) -> f64 {
+        let count = readings.get();
+        readings.set(count.saturating_add(1));
+
+        origin + f64::from(count) * step
+    }
+
+    // Seconds since the Unix epoch
+    pub fn now(&self) -> f64 {
+        match self.fake {
+            Some(fake) => Self::fake_reading(fake.start, fake.step, &self.now_readings),
+            None => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs_f64(),
+        }
+    }
+
+    // Seconds since the interpreter started, from a monotonic clock
+    pub fn elapsed(&self) -> f64 {
+        match self.fake {
+            Some(fake) => Self::fake_reading(0.0, fake.step, &self.elapsed_readings),
+            None => self.started.elapsed().as_secs_f64(),
+        }
+    }
+}
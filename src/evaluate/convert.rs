@@ -0,0 +1,85 @@
+use super::{Error, Literal, Value};
+
+impl From<f64> for Value {
+    fn from(number: f64) -> Self {
+        Value::Literal(Literal::Number(number))
+    }
+}
+
+impl From<bool> for Value {
+    fn from(bool: bool) -> Self {
+        Value::Literal(Literal::Boolean(bool))
+    }
+}
+
+impl From<String> for Value {
+    fn from(string: String) -> Self {
+        Value::Literal(Literal::String(string.into()))
+    }
+}
+
+impl From<&str> for Value {
+    fn from(string: &str) -> Self {
+        Value::from(string.to_string())
+    }
+}
+
+// `None` becomes nil
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(option: Option<T>) -> Self {
+        option.map_or(Value::Literal(Literal::Nil), Into::into)
+    }
+}
+
+// Conversion from a Lox value to a Rust type. Errors carry no line, host
+// functions get the line of the call filled in.
+pub trait FromValue: Sized {
+    fn from_value(value: &Value) -> Result<Self, Error>;
+}
+
+fn expected(kind: &str, value: &Value) -> Error {
+    Error::runtime(format!("Expected {kind}, got '{value}'."), 0)
+}
+
+impl FromValue for Value {
+    fn from_value(value: &Value) -> Result<Self, Error> {
+        Ok(value.clone())
+    }
+}
+
+impl FromValue for f64 {
+    fn from_value(value: &Value) -> Result<Self, Error> {
+        match value {
+            Value::Literal(Literal::Number(number)) => Ok(*number),
+            _ => Err(expected("a number", value)),
+        }
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(value: &Value) -> Result<Self, Error> {
+        match value {
+            Value::Literal(Literal::Boolean(bool)) => Ok(*bool),
+            _ => Err(expected("a boolean", value)),
+        }
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: &Value) -> Result<Self, Error> {
+        match value {
+            Value::Literal(Literal::String(string)) => Ok(string.to_string()),
+            _ => Err(expected("a string", value)),
+        }
+    }
+}
+
+// nil becomes `None`
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: &Value) -> Result<Self, Error> {
+        match value {
+            Value::Literal(Literal::Nil) => Ok(None),
+            value => T::from_value(value).map(Some),
+        }
+    }
+}
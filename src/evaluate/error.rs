@@ -0,0 +1,134 @@
+use core::fmt;
+
+use super::Value;
+
+// A Lox function an error unwound through and the line it was called from.
+// `None` stands for the functions whose frames were reused by tail calls.
+#[derive(Clone, Debug)]
+pub struct Frame {
+    pub function: Option<String>,
+    pub line: usize,
+}
+
+// Frames are innermost first
+#[derive(Clone, Debug)]
+pub enum Error {
+    Runtime {
+        message: String,
+        line: usize,
+        trace: Vec<Frame>,
+    },
+    Throw {
+        value: Value,
+        line: usize,
+        trace: Vec<Frame>,
+    },
+    // Raised when a run uses up its step, time or memory budget; like `Exit`
+    // it is never caught, so a script can't keep running past its limits
+    Limit {
+        message: String,
+        line: usize,
+        trace: Vec<Frame>,
+    },
+    // Raised by `exit()`; never caught so it unwinds to whoever started the script
+    Exit(i32),
+}
+
+impl Error {
+    pub fn runtime(message: impl Into<String>, line: usize) -> Self {
+        Error::Runtime {
+            message: message.into(),
+            line,
+            trace: Vec::new(),
+        }
+    }
+
+    pub fn limit(message: impl Into<String>, line: usize) -> Self {
+        Error::Limit {
+            message: message.into(),
+            line,
+            trace: Vec::new(),
+        }
+    }
+
+    // Whether `try`/`catch` may handle this error
+    pub fn is_catchable(&self) -> bool {
+        matches!(self, Error::Runtime { .. } | Error::Throw { .. })
+    }
+
+    pub fn unwind(mut self, function: &str, line: usize) -> Self {
+        if let Error::Runtime { trace, .. }
+        | Error::Throw { trace, .. }
+        | Error::Limit { trace, .. } = &mut self
+        {
+            trace.push(Frame {
+                function: Some(function.to_string()),
+                line,
+            });
+        }
+
+        self
+    }
+
+    // Stands in for the frames tail calls replaced, back to the call made on `line`
+    pub fn elide(mut self, line: usize) -> Self {
+        if let Error::Runtime { trace, .. }
+        | Error::Throw { trace, .. }
+        | Error::Limit { trace, .. } = &mut self
+        {
+            trace.push(Frame {
+                function: None,
+                line,
+            });
+        }
+
+        self
+    }
+
+    // `[line N] in function()` for each frame, innermost first and ending with
+    // the top level of the script
+    pub fn stack_trace(&self) -> Vec<String> {
+        let (Error::Runtime { line, trace, .. }
+        | Error::Throw { line, trace, .. }
+        | Error::Limit { line, trace, .. }) = self
+        else {
+            return Vec::new();
+        };
+
+        let mut lines = Vec::new();
+        let mut line = *line;
+
+        for frame in trace {
+            match &frame.function {
+                Some(function) => lines.push(format!("[line {line}] in {function}()")),
+                None => lines.push(format!("[line {line}] ... tail calls ...")),
+            }
+
+            line = frame.line;
+        }
+
+        lines.push(format!("[line {line}] in script"));
+        lines
+    }
+
+    pub fn line(&self) -> usize {
+        match self {
+            Error::Runtime { line, .. } | Error::Throw { line, .. } | Error::Limit { line, .. } => {
+                *line
+            }
+            Error::Exit(_) => 0,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Runtime { message, .. } | Error::Limit { message, .. } => {
+                write!(fmt, "{message}")
+            }
+            Error::Throw { value, .. } => write!(fmt, "{value}"),
+            Error::Exit(code) => write!(fmt, "Exit with status {code}."),
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::{
+    cell::Cell,
+    hint,
+    time::{Duration, Instant},
+};
+
+use super::{Error, Options};
+use crate::memory;
+
+// Native stack a run may grow by before "Stack overflow." is raised. The check
+// is made as each Lox call starts, so this leaves room for the call in progress
+// and the host's own frames on a 2 MiB thread, the size `cargo test` and most
+// thread pools use; run on a bigger thread and raise it for deeper recursion.
+pub const DEFAULT_MAX_STACK: usize = 1024 * 1024;
+
+// Checking the clock on every step would dominate tight loops; a power of two
+// so the check is a mask
+const CLOCK_INTERVAL: u64 = 1024;
+
+// Address of a local, to measure how far the native stack has grown
+#[inline(never)]
+fn stack_position() -> usize {
+    let marker = 0u8;
+    hint::black_box(&marker) as *const u8 as usize
+}
+
+// Steps are loop iterations and function calls, the only things that let a
+// script run for longer than its source is long
+#[derive(Debug)]
+pub struct Limits {
+    max_steps: Option<u64>,
+    max_depth: Option<usize>,
+    max_stack: usize,
+    timeout: Option<Duration>,
+    max_memory: Option<usize>,
+    steps: Cell<u64>,
+    depth: Cell<usize>,
+    deadline: Cell<Option<Instant>>,
+    // Where the native stack was when the run started
+    stack_start: Cell<usize>,
+    // Live bytes when the run started, so the limit covers what the script allocates
+    baseline: Cell<isize>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self::new(&Options::default())
+    }
+}
+
+impl Limits {
+    pub fn new(options: &Options) -> Self {
+        Self {
+            max_steps: options.max_steps,
+            max_depth: options.max_depth,
+            max_stack: options.max_stack.unwrap_or(DEFAULT_MAX_STACK),
+            timeout: options.timeout,
+            max_memory: options.max_memory,
+            steps: Cell::new(0),
+            depth: Cell::new(0),
+            deadline: Cell::new(None),
+            stack_start: Cell::new(stack_position()),
+            baseline: Cell::new(0),
+        }
+    }
+
+    // Each top-level run gets a fresh budget; calls back into the interpreter
+    // from a host function share the budget of the script that made them
+    pub fn start(&self) -> Result<(), Error> {
+        if self.depth.get() > 0 {
+            return Ok(());
+        }
+
+        self.steps.set(0);
+        self.stack_start.set(stack_position());
+        self.deadline
+            .set(self.timeout.map(|timeout| Instant::now() + timeout));
+
+        if self.max_memory.is_some() {
+            let Some(live) = memory::live_bytes() else {
+                return Err(Error::runtime(
+                    "A memory limit needs CountingAllocator as the global allocator.",
+                    0,
+                ));
+            };
+
+            self.baseline.set(live);
+        }
+
+        Ok(())
+    }
+
+    // Fails if allocating `bytes` more would go over the memory limit
+    pub fn reserve(&self, bytes: usize, line: usize) -> Result<(), Error> {
+        let Some(max_memory) = self.max_memory else {
+            return Ok(());
+        };
+
+        let used = memory::live_bytes().unwrap_or(0) - self.baseline.get();
+
+        #[allow(clippy::cast_sign_loss)]
+        let used = used.max(0) as usize;
+
+        if used.saturating_add(bytes) > max_memory {
+            return Err(Error::limit("Out of memory.", line));
+        }
+
+        Ok(())
+    }
+
+    // Fails if growing a buffer holding `len` of `capacity` bytes by `additional`
+    // would go over the memory limit; a full buffer is moved into one at least
+    // twice as big
+    pub fn reserve_growth(
+        &self,
+        (len, capacity): (usize, usize),
+        additional: usize,
+        line: usize,
+    ) -> Result<(), Error> {
+        let needed = len.saturating_add(additional);
+
+        if needed <= capacity {
+            return Ok(());
+        }
+
+        self.reserve(needed.max(capacity.saturating_mul(2)), line)
+    }
+
+    pub fn step(&self, line: usize) -> Result<(), Error> {
+        let steps = self.steps.get() + 1;
+        self.steps.set(steps);
+
+        if matches!(self.max_steps, Some(max_steps) if steps > max_steps) {
+            return Err(Error::limit("Step limit exceeded.", line));
+        }
+
+        self.reserve(0, line)?;
+
+        if steps & (CLOCK_INTERVAL - 1) == 0
+            && matches!(self.deadline.get(), Some(deadline) if Instant::now() >= deadline)
+        {
+            return Err(Error::limit("Timeout exceeded.", line));
+        }
+
+        Ok(())
+    }
+
+    pub fn enter_call(&self, line: usize) -> Result<(), Error> {
+        let stack = stack_position().abs_diff(self.stack_start.get());
+
+        if stack > self.max_stack
+            || matches!(self.max_depth, Some(max_depth) if self.depth.get() >= max_depth)
+        {
+            return Err(Error::runtime("Stack overflow.", line));
+        }
+
+        self.depth.set(self.depth.get() + 1);
+        Ok(())
+    }
+
+    pub fn leave_call(&self) {
+        self.depth.set(self.depth.get() - 1);
+    }
+}
@@ -1,10 +1,12 @@
 use core::fmt;
+use std::rc::Rc;
 
 #[derive(Clone, Debug)]
 pub enum Literal {
     Boolean(bool),
     Number(f64),
-    String(String),
+    // Shared, so copying a string value never copies its contents
+    String(Rc<str>),
     Nil,
 }
 
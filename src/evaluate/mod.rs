@@ -1,37 +1,206 @@
+mod clock;
+mod convert;
+mod error;
+mod limits;
 mod literal;
+mod module;
+mod native;
+mod optimize;
+mod output;
+mod prng;
+mod resolve;
+mod runtime;
 mod scope;
 mod value;
 
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
-
-pub(crate) use literal::Literal;
+use std::{io, rc::Rc};
+
+pub use clock::FakeClock;
+pub use convert::FromValue;
+pub use error::Error;
+pub use limits::DEFAULT_MAX_STACK;
+pub use literal::Literal;
+pub(crate) use native::host;
+pub(crate) use optimize::optimize;
+pub(crate) use resolve::resolve;
+pub(crate) use runtime::Runtime;
+pub use runtime::{OptLevel, Options};
 pub(crate) use scope::Scope;
-pub(crate) use value::Value;
+pub(crate) use value::Flow;
+use value::TailCall;
+pub use value::Value;
 
-use crate::parser::{BinaryOperator, Expr, UnaryOperator};
+use crate::parser::{BinaryOperator, Expr, Slot, UnaryOperator};
 
-fn value_to_literal(value: &Value) -> &Literal {
+fn value_to_literal(value: &Value, line: usize) -> Result<&Literal, Error> {
     match value {
-        Value::Literal(value) => value,
-        Value::Return(value) => value_to_literal(value),
-        Value::Callable(_, _) => {
-            eprintln!("Error: {value} is not literal");
-            std::process::exit(65);
+        Value::Literal(value) => Ok(value),
+        Value::Callable(_, _) | Value::Error(_) | Value::List(_) | Value::Map(_) => {
+            Err(Error::runtime(format!("{value} is not a literal."), line))
         }
     }
 }
 
-#[allow(clippy::too_many_lines)]
-pub fn evaluate(expr: &Expr, scope: &Scope) -> Value {
+fn arithmetic(operator: &BinaryOperator, left: f64, right: f64) -> Value {
+    match operator {
+        BinaryOperator::Star => Value::Literal(Literal::Number(left * right)),
+        BinaryOperator::Slash => Value::Literal(Literal::Number(left / right)),
+        BinaryOperator::Percent => Value::Literal(Literal::Number(left % right)),
+        BinaryOperator::Backslash => Value::Literal(Literal::Number((left / right).trunc())),
+        BinaryOperator::StarStar => Value::Literal(Literal::Number(left.powf(right))),
+        BinaryOperator::Plus => Value::Literal(Literal::Number(left + right)),
+        BinaryOperator::Minus => Value::Literal(Literal::Number(left - right)),
+        BinaryOperator::Greater => Value::Literal(Literal::Boolean(left > right)),
+        BinaryOperator::GreaterEqual => Value::Literal(Literal::Boolean(left >= right)),
+        BinaryOperator::Less => Value::Literal(Literal::Boolean(left < right)),
+        BinaryOperator::LessEqual => Value::Literal(Literal::Boolean(left <= right)),
+        BinaryOperator::EqualEqual => Value::Literal(Literal::Boolean(left == right)),
+        BinaryOperator::BangEqual => Value::Literal(Literal::Boolean(left != right)),
+    }
+}
+
+// Number literals and locals holding numbers are read in place, so the most
+// common arithmetic neither recurses into `evaluate` nor clones a `Value`
+fn number_operand(expr: &Expr, scope: &Scope) -> Option<f64> {
     match expr {
+        Expr::Number(number) => Some(*number),
+        Expr::Identifier(_, Some(slot), _) => scope.number(*slot),
+        _ => None,
+    }
+}
+
+pub fn output_error(error: &io::Error, line: usize) -> Error {
+    Error::runtime(format!("Could not write output: {error}."), line)
+}
+
+pub fn undefined_variable(name: &str, line: usize) -> Error {
+    Error::runtime(format!("Undefined variable '{name}'."), line)
+}
+
+// Natives have no closure scope of their own and run in the caller's
+fn invoke(callee: &Value, args: Vec<Value>, scope: &Scope, line: usize) -> Result<Flow, Error> {
+    let Value::Callable(callable, function_scope) = callee else {
+        return Err(Error::runtime("Can only call functions and classes.", line));
+    };
+
+    callable(args, function_scope.as_ref().unwrap_or(scope), line)
+}
+
+pub fn call(callee: &Value, args: Vec<Value>, scope: &Scope, line: usize) -> Result<Value, Error> {
+    let limits = &scope.runtime().limits;
+
+    limits.step(line)?;
+    limits.enter_call(line)?;
+
+    let mut result = invoke(callee, args, scope, line);
+    let mut tail_called = false;
+
+    // A function ending in a tail call hands it back here instead of making it,
+    // so tail recursion runs without growing the native stack
+    let result = loop {
+        match result {
+            Ok(Flow::TailCall(tail)) => {
+                let TailCall { callee, args, line } = *tail;
+                tail_called = true;
+
+                result = limits
+                    .step(line)
+                    .and_then(|()| invoke(&callee, args, scope, line));
+            }
+            Ok(Flow::Next(value) | Flow::Return(value)) => break Ok(value),
+            Err(error) => break Err(error),
+        }
+    };
+
+    limits.leave_call();
+
+    // The functions that made tail calls are gone, so the trace only notes
+    // them between the last one made and the call that started the chain
+    if tail_called {
+        return result.map_err(|error| error.elide(line));
+    }
+
+    result
+}
+
+// Evaluates the callee and arguments of the last call in `f(1)(2)`, making
+// all the calls before it
+fn last_call(
+    name: &str,
+    slot: Option<Slot>,
+    args: &[Vec<Expr>],
+    scope: &Scope,
+    line: usize,
+) -> Result<(Value, Vec<Value>), Error> {
+    let Some(mut callee) = scope.lookup(name, slot) else {
+        return Err(undefined_variable(name, line));
+    };
+
+    let evaluate_args = |args: &[Expr]| {
+        let mut values = Vec::with_capacity(args.len());
+
+        for arg in args {
+            values.push(evaluate(arg, scope)?);
+        }
+
+        Ok::<_, Error>(values)
+    };
+
+    let (last, rest) = args.split_last().expect("calls have an argument list");
+
+    for args in rest {
+        callee = call(&callee, evaluate_args(args)?, scope, line)?;
+    }
+
+    Ok((callee, evaluate_args(last)?))
+}
+
+// Evaluates an expression, or runs a statement for its value
+#[inline]
+pub fn evaluate(expr: &Expr, scope: &Scope) -> Result<Value, Error> {
+    match execute(expr, scope)? {
+        Flow::Next(value) => Ok(value),
+        flow => finish(flow, scope),
+    }
+}
+
+// The value a statement ends with, making a tail call handed back outside a
+// function body where there is no trampoline to take it
+pub fn finish(flow: Flow, scope: &Scope) -> Result<Value, Error> {
+    match flow {
+        Flow::Next(value) | Flow::Return(value) => Ok(value),
+        Flow::TailCall(tail) => call(&tail.callee, tail.args, scope, tail.line),
+    }
+}
+
+#[allow(clippy::too_many_lines)]
+pub fn execute(expr: &Expr, scope: &Scope) -> Result<Flow, Error> {
+    let value = match expr {
         Expr::True => Value::Literal(Literal::Boolean(true)),
         Expr::False => Value::Literal(Literal::Boolean(false)),
         Expr::Nil | Expr::Semicolon => Value::Literal(Literal::Nil),
         Expr::String(string) => Value::Literal(Literal::String(string.clone())),
         Expr::Number(number) => Value::Literal(Literal::Number(*number)),
-        Expr::Unary(operator, expr) => {
-            let value = evaluate(expr, scope);
-            let literal = value_to_literal(&value);
+        Expr::Interpolation(exprs, line) => {
+            let mut string = String::new();
+
+            for expr in exprs {
+                let runtime = scope.runtime();
+                let value = native::display(&evaluate(expr, scope)?, runtime, *line)?;
+
+                runtime.limits.reserve_growth(
+                    (string.len(), string.capacity()),
+                    value.len(),
+                    *line,
+                )?;
+                string.push_str(&value);
+            }
+
+            Value::Literal(Literal::String(string.into()))
+        }
+        Expr::Unary(operator, expr, line) => {
+            let value = evaluate(expr, scope)?;
+            let literal = value_to_literal(&value, *line)?;
 
             match operator {
                 UnaryOperator::Bang => match literal {
@@ -42,233 +211,305 @@ pub fn evaluate(expr: &Expr, scope: &Scope) -> Value {
                 },
                 UnaryOperator::Minus => match literal {
                     Literal::Number(number) => Value::Literal(Literal::Number(-number)),
-                    _ => std::process::exit(70),
+                    _ => return Err(Error::runtime("Operand must be a number.", *line)),
                 },
             }
         }
         Expr::Or(left, right) => {
-            let left = evaluate(left, scope);
-            let literal = value_to_literal(&left);
-            let value = literal.as_bool();
+            let left = evaluate(left, scope)?;
 
-            if value {
-                return left;
+            if left.as_bool() {
+                return Ok(Flow::Next(left));
             }
 
-            evaluate(right, scope)
+            evaluate(right, scope)?
         }
         Expr::And(left, right) => {
-            let left = evaluate(left, scope);
-            let literal = value_to_literal(&left).as_bool();
+            let left = evaluate(left, scope)?;
 
-            if !literal {
-                return left;
+            if !left.as_bool() {
+                return Ok(Flow::Next(left));
             }
 
-            evaluate(right, scope)
+            evaluate(right, scope)?
         }
-        Expr::Binary(operator, left, right) => {
-            let left = evaluate(left, scope);
-            let left = value_to_literal(&left);
+        Expr::Binary(operator, left, right, line) => {
+            let line = *line;
+
+            if let (Some(left), Some(right)) =
+                (number_operand(left, scope), number_operand(right, scope))
+            {
+                return Ok(Flow::Next(arithmetic(operator, left, right)));
+            }
+
+            let left = evaluate(left, scope)?;
+            let right = evaluate(right, scope)?;
+
+            if let (BinaryOperator::EqualEqual | BinaryOperator::BangEqual, false) = (
+                operator,
+                matches!((&left, &right), (Value::Literal(_), Value::Literal(_))),
+            ) {
+                let equal = left.is(&right);
+                let equal = matches!(operator, BinaryOperator::EqualEqual) == equal;
+
+                return Ok(Flow::Next(Value::Literal(Literal::Boolean(equal))));
+            }
 
-            let right = evaluate(right, scope);
-            let right = value_to_literal(&right);
+            let left = value_to_literal(&left, line)?;
+            let right = value_to_literal(&right, line)?;
 
             match (left, right) {
-                (Literal::Number(left), Literal::Number(right)) => match *operator {
-                    BinaryOperator::Star => Value::Literal(Literal::Number(left * right)),
-                    BinaryOperator::Slash => Value::Literal(Literal::Number(left / right)),
-                    BinaryOperator::Plus => Value::Literal(Literal::Number(left + right)),
-                    BinaryOperator::Minus => Value::Literal(Literal::Number(left - right)),
-                    BinaryOperator::Greater => Value::Literal(Literal::Boolean(left > right)),
-                    BinaryOperator::GreaterEqual => Value::Literal(Literal::Boolean(left >= right)),
-                    BinaryOperator::Less => Value::Literal(Literal::Boolean(left < right)),
-                    BinaryOperator::LessEqual => Value::Literal(Literal::Boolean(left <= right)),
-                    BinaryOperator::EqualEqual => Value::Literal(Literal::Boolean(left.eq(right))),
-                    BinaryOperator::BangEqual => Value::Literal(Literal::Boolean(left.ne(right))),
-                },
+                (Literal::Number(left), Literal::Number(right)) => {
+                    arithmetic(operator, *left, *right)
+                }
                 (Literal::String(left), Literal::String(right)) => match *operator {
                     BinaryOperator::Plus => {
-                        Value::Literal(Literal::String(format!("{left}{right}")))
+                        scope
+                            .runtime()
+                            .limits
+                            .reserve(left.len() + right.len(), line)?;
+
+                        Value::Literal(Literal::String(format!("{left}{right}").into()))
                     }
                     BinaryOperator::EqualEqual => Value::Literal(Literal::Boolean(left == right)),
                     BinaryOperator::BangEqual => Value::Literal(Literal::Boolean(left != right)),
-                    _ => std::process::exit(70),
-                },
-                (Literal::Number(_), Literal::String(_))
-                | (Literal::String(_), Literal::Number(_)) => match *operator {
-                    BinaryOperator::EqualEqual | BinaryOperator::BangEqual => {
-                        Value::Literal(Literal::Boolean(false))
-                    }
-                    _ => std::process::exit(70),
+                    _ => return Err(Error::runtime("Operands must be numbers.", line)),
                 },
                 (Literal::Boolean(left), Literal::Boolean(right)) => match *operator {
                     BinaryOperator::EqualEqual => Value::Literal(Literal::Boolean(left == right)),
                     BinaryOperator::BangEqual => Value::Literal(Literal::Boolean(left != right)),
-                    _ => std::process::exit(70),
+                    _ => return Err(Error::runtime("Operands must be numbers.", line)),
                 },
-                _ => std::process::exit(70),
-            }
-        }
-        Expr::Grouping(expr) => evaluate(expr, scope),
-        Expr::Identifier(name) => scope.get(name),
-        Expr::Callable(name, args) => {
-            if let Value::Callable(callable, function_scope) = scope.get(name) {
-                let mut callable = callable;
-                let mut value = Value::Literal(Literal::Nil);
-
-                let mut callable_scope = scope.clone();
-
-                if let Some(function_scope) = function_scope {
-                    callable_scope = function_scope;
-                }
-
-                for args in args {
-                    value = callable(args.clone(), callable_scope.clone(), scope.clone());
-
-                    if let Value::Callable(closure, scope) = value.clone() {
-                        callable = closure;
-
-                        if let Some(scope) = scope {
-                            callable_scope = scope;
-                        }
+                (Literal::Nil, Literal::Nil) => match *operator {
+                    BinaryOperator::EqualEqual => Value::Literal(Literal::Boolean(true)),
+                    BinaryOperator::BangEqual => Value::Literal(Literal::Boolean(false)),
+                    _ => return Err(Error::runtime("Operands must be numbers.", line)),
+                },
+                _ => match *operator {
+                    BinaryOperator::EqualEqual => Value::Literal(Literal::Boolean(false)),
+                    BinaryOperator::BangEqual => Value::Literal(Literal::Boolean(true)),
+                    BinaryOperator::Plus => {
+                        return Err(Error::runtime(
+                            "Operands must be two numbers or two strings.",
+                            line,
+                        ))
                     }
-                }
-
-                value
-            } else {
-                eprintln!("Error: {name} is not callable");
-                std::process::exit(65);
+                    _ => return Err(Error::runtime("Operands must be numbers.", line)),
+                },
             }
         }
-        Expr::Assignment(name, expr, define) => {
+        Expr::Grouping(expr) => evaluate(expr, scope)?,
+        Expr::Identifier(name, slot, line) => scope
+            .lookup(name, *slot)
+            .ok_or_else(|| undefined_variable(name, *line))?,
+        Expr::Callable(name, slot, args, line) => {
+            let (callee, args) = last_call(name, *slot, args, scope, *line)?;
+            call(&callee, args, scope, *line)?
+        }
+        Expr::Assignment(name, slot, expr, define, line) => {
             let expr = expr.as_ref();
 
             if *define {
-                let value = evaluate(expr, scope);
-                scope.define(name.clone(), value.clone());
-                value
-            } else if let Expr::Identifier(expr_name) = expr {
-                let value = scope.get(expr_name);
-                scope.set(name, value.clone());
+                let value = evaluate(expr, scope)?;
+                scope.declare(name, *slot, value.clone());
                 value
             } else {
-                let value = evaluate(expr, scope);
-                scope.set(name, value.clone());
+                let value = evaluate(expr, scope)?;
+
+                if !scope.assign(name, *slot, value.clone()) {
+                    return Err(undefined_variable(name, *line));
+                }
+
                 value
             }
         }
-        Expr::Print(expr) => {
-            let result = evaluate(expr, scope);
-            println!("{result}");
+        Expr::Postfix(name, slot, expr, line) => {
+            let value = scope
+                .lookup(name, *slot)
+                .ok_or_else(|| undefined_variable(name, *line))?;
+            evaluate(expr, scope)?;
+            value
+        }
+        Expr::Print(expr, line) => {
+            let result = evaluate(expr, scope)?;
+
+            scope
+                .runtime()
+                .write(&format!("{result}\n"))
+                .map_err(|error| output_error(&error, *line))?;
+
             Value::Literal(Literal::Nil)
         }
-        Expr::Statements(exprs) => {
+        Expr::Statements(exprs, locals) => {
             let mut statement = Value::Literal(Literal::Nil);
-            let scope = Scope::new(HashMap::new(), Some(Rc::new(RefCell::new(scope.clone()))));
-
-            for expr in exprs {
-                statement = evaluate(expr, &scope);
+            let block;
 
-                if let Value::Return(value) = &statement {
-                    if let Value::Callable(callable, _) = &**value {
-                        return Value::Callable(callable.clone(), Some(scope.clone()));
-                    }
+            let scope = if locals.is_empty() {
+                scope
+            } else {
+                block = scope.block(locals);
+                &block
+            };
 
-                    return statement;
+            for expr in exprs {
+                match execute(expr, scope)? {
+                    Flow::Next(value) => statement = value,
+                    flow => return Ok(flow),
                 }
             }
 
             statement
         }
         Expr::IfElse(expr1, expr2, expr3) => {
-            let statement = evaluate(expr1, scope);
-            let statement = value_to_literal(&statement);
+            let statement = evaluate(expr1, scope)?;
 
             if statement.as_bool() {
-                return evaluate(expr2, scope);
+                return execute(expr2, scope);
             } else if let Some(else_expr) = expr3 {
-                return evaluate(else_expr, scope);
+                return execute(else_expr, scope);
             }
 
             Value::Literal(Literal::Nil)
         }
-        Expr::While(expr1, expr2) => {
-            while value_to_literal(&evaluate(expr1, scope)).as_bool() {
-                let statement = evaluate(expr2, scope);
-                if let Value::Return(_) = &statement {
-                    return statement;
+        Expr::Ternary(condition, then, otherwise) => {
+            let condition = evaluate(condition, scope)?;
+
+            if condition.as_bool() {
+                evaluate(then, scope)?
+            } else {
+                evaluate(otherwise, scope)?
+            }
+        }
+        Expr::While(expr1, expr2, line) => {
+            while evaluate(expr1, scope)?.as_bool() {
+                scope.runtime().limits.step(*line)?;
+
+                let flow = execute(expr2, scope)?;
+                if !matches!(flow, Flow::Next(_)) {
+                    return Ok(flow);
                 }
             }
 
             Value::Literal(Literal::Nil)
         }
-        Expr::For(expr1, expr2, expr3, expr4) => {
+        Expr::For(expr1, expr2, expr3, expr4, line) => {
             if let Some(expr1) = expr1 {
-                evaluate(expr1, scope);
+                evaluate(expr1, scope)?;
             }
 
             if let Some(expr2) = expr2 {
-                while value_to_literal(&evaluate(expr2, scope)).as_bool() {
-                    let statement = evaluate(expr4, scope);
-                    if let Value::Return(_) = &statement {
-                        return statement;
+                while evaluate(expr2, scope)?.as_bool() {
+                    scope.runtime().limits.step(*line)?;
+
+                    let flow = execute(expr4, scope)?;
+                    if !matches!(flow, Flow::Next(_)) {
+                        return Ok(flow);
                     }
 
                     if let Some(expr3) = expr3 {
-                        evaluate(expr3, scope);
+                        evaluate(expr3, scope)?;
                     }
                 }
             }
 
             Value::Literal(Literal::Nil)
         }
-        Expr::Fun(name, args, expr) => {
-            let expr = expr.as_ref().clone();
-            let expr = RefCell::new(expr);
-            let args = args.clone();
-
-            let closure = move |values: Vec<Expr>, function_scope: Scope, args_scope: Scope| {
-                let args = args.clone();
-
-                if values.len() != args.len() {
-                    eprintln!(
-                        "Error: Expected {} arguments but got {}",
-                        args.len(),
-                        values.len()
-                    );
-                    std::process::exit(70);
+        Expr::Fun(name, slot, args, expr, locals) => {
+            let expr = expr.clone();
+            let locals = locals.clone();
+            let arity = args.len();
+            let function = name.clone();
+
+            let closure = move |values: Vec<Value>, function_scope: &Scope, line: usize| {
+                if values.len() != arity {
+                    return Err(Error::runtime(
+                        format!("Expected {arity} arguments but got {}.", values.len()),
+                        line,
+                    ));
                 }
 
-                let function_scope = Scope::new(
-                    HashMap::new(),
-                    Some(Rc::new(RefCell::new(function_scope.clone()))),
-                );
-
-                let expr = expr.borrow();
+                let function_scope = function_scope.frame(&locals, values);
 
-                for (index, arg) in args.iter().enumerate() {
-                    let value_expr = values.get(index).unwrap();
-                    let value = evaluate(value_expr, &args_scope);
-                    function_scope.define(arg.clone(), value);
-                }
-
-                let value = evaluate(&expr, &function_scope);
+                let flow = execute(&expr, &function_scope)
+                    .map_err(|error| error.unwind(&function, line))?;
 
-                match value {
-                    Value::Return(value) => *value,
-                    value => value,
+                match flow {
+                    Flow::Return(value) => Ok(Flow::Next(value)),
+                    flow => Ok(flow),
                 }
             };
 
             let closure = Rc::new(closure);
 
-            scope.define(name.clone(), Value::Callable(closure, Some(scope.clone())));
+            scope.declare(name, *slot, Value::Callable(closure, Some(scope.clone())));
             Value::Literal(Literal::Nil)
         }
         Expr::Return(expr) => {
-            let value = evaluate(expr, scope);
-            Value::Return(Box::new(value))
+            let value = evaluate(expr, scope)?;
+            return Ok(Flow::Return(value));
         }
-    }
+        Expr::TailCall(name, slot, args, line) => {
+            let (callee, args) = last_call(name, *slot, args, scope, *line)?;
+
+            return Ok(Flow::TailCall(Box::new(TailCall {
+                callee,
+                args,
+                line: *line,
+            })));
+        }
+        Expr::Throw(expr, line) => {
+            let value = evaluate(expr, scope)?;
+
+            // Rethrowing a caught runtime error keeps its original message and line
+            if let Value::Error(error) = value {
+                return Err(error.as_ref().clone());
+            }
+
+            return Err(Error::Throw {
+                value,
+                line: *line,
+                trace: Vec::new(),
+            });
+        }
+        Expr::Import(path, names, line) => {
+            module::import(path, names.as_deref(), scope, *line)?;
+            Value::Literal(Literal::Nil)
+        }
+        Expr::Try(body, catch, finally) => {
+            // Exits and exhausted limits skip both `catch` and `finally`
+            let uncatchable = |result: &Result<Flow, Error>| matches!(result, Err(error) if !error.is_catchable());
+            let mut result = execute(body, scope);
+
+            if uncatchable(&result) {
+                return result;
+            }
+
+            if let (Err(error), Some((name, handler))) = (&result, catch) {
+                let value = match error {
+                    Error::Throw { value, .. } => value.clone(),
+                    error => Value::Error(Rc::new(error.clone())),
+                };
+
+                let scope = scope.frame(&Rc::from([name.clone()]), vec![value]);
+
+                result = execute(handler, &scope);
+
+                if uncatchable(&result) {
+                    return result;
+                }
+            }
+
+            if let Some(finally) = finally {
+                let flow = execute(finally, scope)?;
+
+                if !matches!(flow, Flow::Next(_)) {
+                    return Ok(flow);
+                }
+            }
+
+            return result;
+        }
+    };
+
+    Ok(Flow::Next(value))
 }
@@ -0,0 +1,111 @@
+use std::{fs, path::PathBuf};
+
+use super::{evaluate, optimize, runtime::Exports, Error, Expr, Scope};
+use crate::{parser, scanner};
+
+fn resolve(path: &str, scope: &Scope, line: usize) -> Result<PathBuf, Error> {
+    let base = scope
+        .runtime()
+        .current_file()
+        .and_then(|file| file.parent().map(PathBuf::from))
+        .unwrap_or_default();
+
+    fs::canonicalize(base.join(path))
+        .map_err(|_| Error::runtime(format!("Could not find module '{path}'."), line))
+}
+
+fn load(path: &str, file: PathBuf, scope: &Scope, line: usize) -> Result<Exports, Error> {
+    let runtime = scope.runtime();
+
+    let source = fs::read_to_string(&file)
+        .map_err(|_| Error::runtime(format!("Could not read module '{path}'."), line))?;
+
+    let scan_tokens = scanner::scan_tokens(&source);
+
+    if let Some(error) = scan_tokens.errors.first() {
+        return Err(Error::runtime(
+            format!(
+                "Could not import '{path}': [line {}] Error: {}",
+                error.line, error.message
+            ),
+            line,
+        ));
+    }
+
+    let statements = parser::parse_statements(&scan_tokens.tokens)
+        .map_err(|error| Error::runtime(format!("Could not import '{path}': {error}"), line))?;
+    let statements = super::resolve(optimize(statements, runtime.options.opt_level));
+    let module = Scope::module(runtime.clone());
+
+    runtime.enter(file.clone());
+
+    let result = statements
+        .iter()
+        .try_for_each(|expr| evaluate(expr, &module).map(|_| ()));
+
+    runtime.leave();
+    result?;
+
+    // Only top-level declarations are visible to importers
+    let mut exports = Exports::new();
+
+    for expr in &statements {
+        if let Expr::Assignment(name, _, _, true, _) | Expr::Fun(name, ..) = expr {
+            if let Some(value) = module.get(name) {
+                exports.insert(name.clone(), value);
+            }
+        }
+    }
+
+    runtime.cache_module(file, exports.clone());
+
+    Ok(exports)
+}
+
+pub fn import(
+    path: &str,
+    names: Option<&[String]>,
+    scope: &Scope,
+    line: usize,
+) -> Result<(), Error> {
+    let runtime = scope.runtime();
+    let file = resolve(path, scope, line)?;
+
+    if let Some(chain) = runtime.import_chain(&file) {
+        let chain = chain
+            .iter()
+            .chain([&file])
+            .map(|file| file.display().to_string())
+            .collect::<Vec<_>>()
+            .join(" -> ");
+
+        return Err(Error::runtime(format!("Import cycle: {chain}."), line));
+    }
+
+    let exports = match runtime.module(&file) {
+        Some(exports) => exports,
+        None => load(path, file, scope, line)?,
+    };
+
+    match names {
+        Some(names) => {
+            for name in names {
+                let Some(value) = exports.get(name) else {
+                    return Err(Error::runtime(
+                        format!("Module '{path}' has no export '{name}'."),
+                        line,
+                    ));
+                };
+
+                scope.define(name.clone(), value.clone());
+            }
+        }
+        None => {
+            for (name, value) in exports {
+                scope.define(name, value);
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use std::rc::Rc;
+
+use super::{define, integer, list, string, Literal, Scope, Value};
+
+pub fn globals(scope: &Scope) {
+    // Lists are indexed by position, maps by key; a missing key gives nil
+    define(scope, "get", 2, |args| {
+        if let Value::Map(entries) = &args[0] {
+            let key = string(args, 1)?;
+            return Ok(entries
+                .get(key)
+                .cloned()
+                .unwrap_or(Value::Literal(Literal::Nil)));
+        }
+
+        let values = list(args, 0)?;
+        let index = integer(args, 1)?;
+
+        values
+            .get(index)
+            .cloned()
+            .ok_or_else(|| format!("Index {index} is out of range."))
+    });
+
+    define(scope, "keys", 1, |args| match &args[0] {
+        Value::Map(entries) => Ok(Value::List(Rc::new(
+            entries
+                .keys()
+                .map(|key| Value::Literal(Literal::String(key.clone().into())))
+                .collect(),
+        ))),
+        value => Err(format!("Argument 1 must be a map, got '{value}'.")),
+    });
+}
@@ -0,0 +1,24 @@
+use super::{define, Error, Literal, Scope, Value};
+
+fn error(args: &[Value]) -> Result<&Error, String> {
+    match &args[0] {
+        Value::Error(error) => Ok(error),
+        value => Err(format!(
+            "Argument 1 must be a caught runtime error, got '{value}'."
+        )),
+    }
+}
+
+// For the runtime errors `catch` receives; thrown values are caught as they are
+pub fn globals(scope: &Scope) {
+    define(scope, "error_message", 1, |args| {
+        Ok(Value::Literal(Literal::String(
+            error(args)?.to_string().into(),
+        )))
+    });
+
+    define(scope, "error_line", 1, |args| {
+        #[allow(clippy::cast_precision_loss)]
+        Ok(Value::Literal(Literal::Number(error(args)?.line() as f64)))
+    });
+}
@@ -0,0 +1,159 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Component, Path, PathBuf},
+    rc::Rc,
+};
+
+use super::{define_with_runtime, string, Literal, Scope, Value};
+use crate::evaluate::Runtime;
+
+// Resolves as much of `path` as exists and appends the missing rest as
+// written, or gives up when the rest can't be placed without resolving it
+fn resolve_existing(path: &Path) -> Option<PathBuf> {
+    let mut missing = vec![];
+
+    for ancestor in path.ancestors() {
+        let ancestor = if ancestor.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            ancestor
+        };
+
+        if let Ok(resolved) = fs::canonicalize(ancestor) {
+            return Some(
+                missing
+                    .iter()
+                    .rev()
+                    .fold(resolved, |path, name| path.join(name)),
+            );
+        }
+
+        match ancestor.components().next_back() {
+            Some(Component::Normal(name)) => missing.push(name),
+            _ => return None,
+        }
+    }
+
+    None
+}
+
+// Resolves `path` (which may not exist yet) and checks that it lies inside
+// one of the allowed roots. Symlinks are followed so they can't escape them,
+// and a dangling one is refused since opening it would create its target.
+// Anything outside the roots gets the same error, so scripts can't probe it.
+fn sandbox(runtime: &Runtime, path: &str) -> Result<PathBuf, String> {
+    let denied = || format!("Access to '{path}' is not allowed.");
+    let allowed = |resolved: &Path| {
+        runtime
+            .options
+            .fs_roots
+            .iter()
+            .any(|root| resolved.starts_with(root))
+    };
+
+    if let Ok(resolved) = fs::canonicalize(path) {
+        return if allowed(&resolved) {
+            Ok(resolved)
+        } else {
+            Err(denied())
+        };
+    }
+
+    let path = Path::new(path);
+    let name = path.file_name().ok_or_else(denied)?;
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    match fs::canonicalize(parent) {
+        Ok(parent) => {
+            let resolved = parent.join(name);
+
+            // Something is there even though it couldn't be resolved
+            if !allowed(&resolved) || fs::symlink_metadata(&resolved).is_ok() {
+                return Err(denied());
+            }
+
+            Ok(resolved)
+        }
+        Err(error) => match resolve_existing(parent) {
+            Some(resolved) if allowed(&resolved) => Err(format!(
+                "Could not resolve '{}': {error}.",
+                parent.display()
+            )),
+            _ => Err(denied()),
+        },
+    }
+}
+
+fn write(runtime: &Runtime, args: &[Value], append: bool) -> Result<Value, String> {
+    let path = string(args, 0)?;
+    let content = string(args, 1)?;
+    let resolved = sandbox(runtime, path)?;
+
+    OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(resolved)
+        .and_then(|mut file| file.write_all(content.as_bytes()))
+        .map_err(|error| format!("Could not write '{path}': {error}."))?;
+
+    Ok(Value::Literal(Literal::Nil))
+}
+
+pub fn globals(scope: &Scope) {
+    define_with_runtime(scope, "read_file", 1, |args, runtime, line| {
+        let path = string(args, 0)?;
+        let resolved = sandbox(runtime, path)?;
+        let error = |error| format!("Could not read '{path}': {error}.");
+
+        let size = fs::metadata(&resolved).map_err(error)?.len();
+        runtime
+            .limits
+            .reserve(usize::try_from(size).unwrap_or(usize::MAX), line)?;
+
+        Ok(fs::read_to_string(resolved)
+            .map(|content| Value::Literal(Literal::String(content.into())))
+            .map_err(error)?)
+    });
+
+    define_with_runtime(scope, "write_file", 2, |args, runtime, _line| {
+        Ok(write(runtime, args, false)?)
+    });
+
+    define_with_runtime(scope, "append_file", 2, |args, runtime, _line| {
+        Ok(write(runtime, args, true)?)
+    });
+
+    define_with_runtime(scope, "file_exists", 1, |args, runtime, _line| {
+        let resolved = sandbox(runtime, string(args, 0)?)?;
+        Ok(Value::Literal(Literal::Boolean(resolved.exists())))
+    });
+
+    define_with_runtime(scope, "list_dir", 1, |args, runtime, _line| {
+        let path = string(args, 0)?;
+        let error = |error| format!("Could not list '{path}': {error}.");
+
+        let mut names = fs::read_dir(sandbox(runtime, path)?)
+            .map_err(error)?
+            .map(|entry| {
+                entry
+                    .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                    .map_err(error)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        names.sort();
+
+        let names = names
+            .into_iter()
+            .map(|name| Value::Literal(Literal::String(name.into())))
+            .collect();
+
+        Ok(Value::List(Rc::new(names)))
+    });
+}
@@ -0,0 +1,82 @@
+use std::io::{self, BufRead, Read};
+
+use super::{define_with_runtime, string, Literal, Scope, Value};
+use crate::evaluate::Runtime;
+
+// Anything printed so far should be visible before blocking on stdin
+fn flush(runtime: &Runtime) -> Result<(), String> {
+    runtime
+        .flush()
+        .map_err(|error| format!("Could not write output: {error}."))
+}
+
+// Read at a time by `read_all`, so the memory limit is checked as it grows
+const CHUNK_SIZE: usize = 64 * 1024;
+
+// Returns `nil` once stdin is exhausted
+fn read_line() -> Result<Value, String> {
+    let mut line = String::new();
+
+    let read = io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .map_err(|error| format!("Could not read from stdin: {error}."))?;
+
+    if read == 0 {
+        return Ok(Value::Literal(Literal::Nil));
+    }
+
+    if line.ends_with('\n') {
+        line.pop();
+
+        if line.ends_with('\r') {
+            line.pop();
+        }
+    }
+
+    Ok(Value::Literal(Literal::String(line.into())))
+}
+
+pub fn globals(scope: &Scope) {
+    define_with_runtime(scope, "input", 1, |args, runtime, _line| {
+        runtime
+            .write(string(args, 0)?)
+            .map_err(|error| format!("Could not write output: {error}."))?;
+
+        flush(runtime)?;
+        Ok(read_line()?)
+    });
+
+    define_with_runtime(scope, "read_line", 0, |_args, runtime, _line| {
+        flush(runtime)?;
+        Ok(read_line()?)
+    });
+
+    define_with_runtime(scope, "read_all", 0, |_args, runtime, line| {
+        flush(runtime)?;
+
+        let mut content = Vec::new();
+        let mut stdin = io::stdin().lock();
+
+        loop {
+            runtime
+                .limits
+                .reserve_growth((content.len(), content.capacity()), CHUNK_SIZE, line)?;
+
+            let read = (&mut stdin)
+                .take(CHUNK_SIZE as u64)
+                .read_to_end(&mut content)
+                .map_err(|error| format!("Could not read from stdin: {error}."))?;
+
+            if read == 0 {
+                break;
+            }
+        }
+
+        let content = String::from_utf8(content).map_err(|_| {
+            String::from("Could not read from stdin: stream did not contain valid UTF-8.")
+        })?;
+
+        Ok(Value::Literal(Literal::String(content.into())))
+    });
+}
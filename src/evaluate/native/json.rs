@@ -0,0 +1,496 @@
+use std::{collections::BTreeMap, fmt::Write, iter::Peekable, rc::Rc, str::Chars};
+
+use super::{define, define_with_runtime, integer, string, Failure, Literal, Scope, Value};
+use crate::evaluate::limits::Limits;
+
+// Deeper documents are rejected rather than risking a stack overflow
+const MAX_DEPTH: usize = 512;
+
+// Larger indents are clamped, as in JavaScript's `JSON.stringify`
+const MAX_INDENT: usize = 10;
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+    column: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            chars: text.chars().peekable(),
+            line: 1,
+            column: 1,
+            depth: 0,
+        }
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("{message} at line {}, column {}.", self.line, self.column)
+    }
+
+    fn unexpected(&mut self) -> String {
+        match self.chars.peek().copied() {
+            Some(char) => self.error(&format!("Unexpected character '{}'", char.escape_debug())),
+            None => self.error("Unexpected end of input"),
+        }
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let char = self.chars.next()?;
+
+        if char == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+
+        Some(char)
+    }
+
+    fn next_if_eq(&mut self, expected: char) -> bool {
+        if self.chars.peek() == Some(&expected) {
+            self.next();
+            return true;
+        }
+
+        false
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        if self.next_if_eq(expected) {
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    fn whitespace(&mut self) {
+        while matches!(self.chars.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.next();
+        }
+    }
+
+    fn document(&mut self) -> Result<Value, String> {
+        let value = self.value()?;
+        self.whitespace();
+
+        if self.chars.peek().is_some() {
+            return Err(self.unexpected());
+        }
+
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.whitespace();
+
+        match self.chars.peek() {
+            Some('{') => self.nested(Self::object),
+            Some('[') => self.nested(Self::array),
+            Some('"') => Ok(Value::Literal(Literal::String(self.string()?.into()))),
+            Some('-' | '0'..='9') => self.number(),
+            Some('t') => self.keyword("true", Literal::Boolean(true)),
+            Some('f') => self.keyword("false", Literal::Boolean(false)),
+            Some('n') => self.keyword("null", Literal::Nil),
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<Value, String>) -> Result<Value, String> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error("Nesting is too deep"));
+        }
+
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+
+        value
+    }
+
+    fn keyword(&mut self, keyword: &str, literal: Literal) -> Result<Value, String> {
+        for expected in keyword.chars() {
+            self.expect(expected)?;
+        }
+
+        Ok(Value::Literal(literal))
+    }
+
+    fn object(&mut self) -> Result<Value, String> {
+        self.expect('{')?;
+        self.whitespace();
+
+        let mut entries = BTreeMap::new();
+
+        if !self.next_if_eq('}') {
+            loop {
+                self.whitespace();
+
+                if self.chars.peek() != Some(&'"') {
+                    return Err(self.unexpected());
+                }
+
+                let key = self.string()?;
+                self.whitespace();
+                self.expect(':')?;
+
+                entries.insert(key, self.value()?);
+                self.whitespace();
+
+                if self.next_if_eq('}') {
+                    break;
+                }
+
+                self.expect(',')?;
+            }
+        }
+
+        Ok(Value::Map(Rc::new(entries)))
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.expect('[')?;
+        self.whitespace();
+
+        let mut values = Vec::new();
+
+        if !self.next_if_eq(']') {
+            loop {
+                values.push(self.value()?);
+                self.whitespace();
+
+                if self.next_if_eq(']') {
+                    break;
+                }
+
+                self.expect(',')?;
+            }
+        }
+
+        Ok(Value::List(Rc::new(values)))
+    }
+
+    fn digits(&mut self, text: &mut String) -> Result<(), String> {
+        if !matches!(self.chars.peek(), Some('0'..='9')) {
+            return Err(self.unexpected());
+        }
+
+        while let Some(digit @ '0'..='9') = self.chars.peek().copied() {
+            text.push(digit);
+            self.next();
+        }
+
+        Ok(())
+    }
+
+    fn number(&mut self) -> Result<Value, String> {
+        let mut text = String::new();
+
+        if self.next_if_eq('-') {
+            text.push('-');
+        }
+
+        // Leading zeros are not allowed
+        if self.next_if_eq('0') {
+            text.push('0');
+        } else {
+            self.digits(&mut text)?;
+        }
+
+        if self.next_if_eq('.') {
+            text.push('.');
+            self.digits(&mut text)?;
+        }
+
+        if let Some(exponent @ ('e' | 'E')) = self.chars.peek().copied() {
+            text.push(exponent);
+            self.next();
+
+            if let Some(sign @ ('+' | '-')) = self.chars.peek().copied() {
+                text.push(sign);
+                self.next();
+            }
+
+            self.digits(&mut text)?;
+        }
+
+        let number: f64 = text.parse().map_err(|_| self.error("Invalid number"))?;
+
+        if !number.is_finite() {
+            return Err(self.error("Number is out of range"));
+        }
+
+        Ok(Value::Literal(Literal::Number(number)))
+    }
+
+    fn hex(&mut self) -> Result<u32, String> {
+        let mut code = 0;
+
+        for _ in 0..4 {
+            let digit = self.chars.peek().and_then(|char| char.to_digit(16));
+            let Some(digit) = digit else {
+                return Err(self.unexpected());
+            };
+
+            self.next();
+            code = code * 16 + digit;
+        }
+
+        Ok(code)
+    }
+
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.hex()?;
+
+        // Characters outside the Basic Multilingual Plane arrive as a surrogate pair
+        let code = if (0xD800..0xDC00).contains(&high) {
+            self.expect('\\')?;
+            self.expect('u')?;
+
+            let low = self.hex()?;
+
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error("Invalid low surrogate"));
+            }
+
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+
+        char::from_u32(code).ok_or_else(|| self.error("Invalid unicode escape"))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+
+        let mut string = String::new();
+
+        loop {
+            match self.chars.peek().copied() {
+                None => return Err(self.error("Unterminated string")),
+                Some('"') => {
+                    self.next();
+                    return Ok(string);
+                }
+                Some('\\') => {
+                    self.next();
+
+                    let escaped = match self.chars.peek() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            self.next();
+                            string.push(self.unicode_escape()?);
+                            continue;
+                        }
+                        _ => return Err(self.unexpected()),
+                    };
+
+                    self.next();
+                    string.push(escaped);
+                }
+                Some(char) if char < ' ' => {
+                    return Err(self.error("Control character in string"));
+                }
+                Some(char) => {
+                    self.next();
+                    string.push(char);
+                }
+            }
+        }
+    }
+}
+
+fn quote(output: &mut String, string: &str) {
+    output.push('"');
+
+    for char in string.chars() {
+        match char {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            char if char < ' ' => {
+                let _ = write!(output, "\\u{:04x}", char as u32);
+            }
+            char => output.push(char),
+        }
+    }
+
+    output.push('"');
+}
+
+// Bytes `quote` writes for `string`
+fn quoted_len(string: &str) -> usize {
+    string
+        .chars()
+        .map(|char| match char {
+            '"' | '\\' | '\n' | '\r' | '\t' => 2,
+            char if char < ' ' => 6,
+            char => char.len_utf8(),
+        })
+        .sum::<usize>()
+        + 2
+}
+
+fn newline(output: &mut String, indent: usize, depth: usize) -> Result<(), String> {
+    if indent > 0 {
+        let spaces = indent
+            .checked_mul(depth)
+            .ok_or_else(|| String::from("Indentation is too deep."))?;
+
+        output.push('\n');
+        output.push_str(&" ".repeat(spaces));
+    }
+
+    Ok(())
+}
+
+// Writes the items of an array or object, one per line when indenting
+fn items<T>(
+    output: &mut String,
+    (open, close): (char, char),
+    items: impl ExactSizeIterator<Item = T>,
+    indent: usize,
+    depth: usize,
+    mut item: impl FnMut(&mut String, T) -> Result<(), Failure>,
+) -> Result<(), Failure> {
+    output.push(open);
+
+    let empty = items.len() == 0;
+
+    for (index, value) in items.enumerate() {
+        if index > 0 {
+            output.push(',');
+        }
+
+        newline(output, indent, depth + 1)?;
+        item(output, value)?;
+    }
+
+    if !empty {
+        newline(output, indent, depth)?;
+    }
+
+    output.push(close);
+    Ok(())
+}
+
+// Checked before each value is written, so the memory limit stops the output
+// growing rather than being found exceeded once it is built
+struct Budget<'a> {
+    limits: &'a Limits,
+    line: usize,
+}
+
+impl Budget<'_> {
+    fn check(&self, output: &mut String, additional: usize) -> Result<(), Failure> {
+        Ok(self
+            .limits
+            .reserve_growth((output.len(), output.capacity()), additional, self.line)?)
+    }
+}
+
+// Room for a number, a keyword or the punctuation around a value
+const VALUE_SIZE: usize = 32;
+
+fn stringify(
+    output: &mut String,
+    value: &Value,
+    indent: usize,
+    depth: usize,
+    budget: &Budget,
+) -> Result<(), Failure> {
+    if depth == MAX_DEPTH {
+        return Err(String::from("Nesting is too deep.").into());
+    }
+
+    match value {
+        Value::Literal(Literal::String(string)) => budget.check(output, quoted_len(string))?,
+        _ => budget.check(
+            output,
+            VALUE_SIZE.saturating_add(indent.saturating_mul(depth)),
+        )?,
+    }
+
+    match value {
+        Value::Literal(Literal::Nil) => output.push_str("null"),
+        Value::Literal(Literal::Boolean(bool)) => output.push_str(&bool.to_string()),
+        Value::Literal(Literal::Number(number)) => {
+            if !number.is_finite() {
+                return Err(format!("Cannot convert '{number}' to JSON.").into());
+            }
+
+            output.push_str(&number.to_string());
+        }
+        Value::Literal(Literal::String(string)) => quote(output, string),
+        Value::List(values) => {
+            items(
+                output,
+                ('[', ']'),
+                values.iter(),
+                indent,
+                depth,
+                |output, value| stringify(output, value, indent, depth + 1, budget),
+            )?;
+        }
+        Value::Map(entries) => {
+            let separator = if indent > 0 { ": " } else { ":" };
+
+            items(
+                output,
+                ('{', '}'),
+                entries.iter(),
+                indent,
+                depth,
+                |output, (key, value)| {
+                    budget.check(output, quoted_len(key))?;
+                    quote(output, key);
+                    output.push_str(separator);
+                    stringify(output, value, indent, depth + 1, budget)
+                },
+            )?;
+        }
+        Value::Callable(_, _) | Value::Error(_) => {
+            return Err(format!("Cannot convert '{value}' to JSON.").into());
+        }
+    }
+
+    Ok(())
+}
+
+// JSON objects become maps and arrays become lists; `null` is nil
+pub fn globals(scope: &Scope) {
+    define(scope, "json_parse", 1, |args| {
+        Parser::new(string(args, 0)?).document()
+    });
+
+    // An indent of nil or 0 gives compact output
+    define_with_runtime(scope, "json_stringify", 2, |args, runtime, line| {
+        let indent = match &args[1] {
+            Value::Literal(Literal::Nil) => 0,
+            _ => integer(args, 1)?.min(MAX_INDENT),
+        };
+
+        let budget = Budget {
+            limits: &runtime.limits,
+            line,
+        };
+
+        let mut output = String::new();
+        stringify(&mut output, &args[0], indent, 0, &budget)?;
+
+        Ok(Value::Literal(Literal::String(output.into())))
+    });
+}
@@ -0,0 +1,43 @@
+use std::f64::consts;
+
+use super::{define, number, Literal, Scope, Value};
+
+fn unary(scope: &Scope, name: &str, function: fn(f64) -> f64) {
+    define(scope, name, 1, move |args| {
+        Ok(Value::Literal(Literal::Number(function(number(args, 0)?))))
+    });
+}
+
+fn binary(scope: &Scope, name: &str, function: fn(f64, f64) -> f64) {
+    define(scope, name, 2, move |args| {
+        let result = function(number(args, 0)?, number(args, 1)?);
+        Ok(Value::Literal(Literal::Number(result)))
+    });
+}
+
+pub fn globals(scope: &Scope) {
+    unary(scope, "sqrt", f64::sqrt);
+    unary(scope, "abs", f64::abs);
+    unary(scope, "floor", f64::floor);
+    unary(scope, "ceil", f64::ceil);
+    unary(scope, "round", f64::round);
+    unary(scope, "sin", f64::sin);
+    unary(scope, "cos", f64::cos);
+    unary(scope, "tan", f64::tan);
+    unary(scope, "log", f64::ln);
+    unary(scope, "exp", f64::exp);
+
+    binary(scope, "pow", f64::powf);
+    binary(scope, "min", f64::min);
+    binary(scope, "max", f64::max);
+    binary(scope, "atan2", f64::atan2);
+
+    scope.define(
+        String::from("PI"),
+        Value::Literal(Literal::Number(consts::PI)),
+    );
+    scope.define(
+        String::from("E"),
+        Value::Literal(Literal::Number(consts::E)),
+    );
+}
@@ -0,0 +1,207 @@
+mod collections;
+mod error;
+mod fs;
+mod io;
+mod json;
+mod math;
+mod process;
+mod random;
+mod string;
+mod time;
+
+use std::{
+    fmt::{self, Write},
+    rc::Rc,
+};
+
+use super::{Error, Flow, Literal, Runtime, Scope, Value};
+
+pub fn check_arity(args: &[Value], arity: usize, line: usize) -> Result<(), Error> {
+    if args.len() != arity {
+        return Err(Error::runtime(
+            format!("Expected {arity} arguments but got {}.", args.len()),
+            line,
+        ));
+    }
+
+    Ok(())
+}
+
+// Why a native failed: a message, which gets the function name and call line,
+// or an error such as an exhausted memory limit that passes through untouched
+pub enum Failure {
+    Message(String),
+    Error(Error),
+}
+
+impl From<String> for Failure {
+    fn from(message: String) -> Self {
+        Failure::Message(message)
+    }
+}
+
+impl From<Error> for Failure {
+    fn from(error: Error) -> Self {
+        Failure::Error(error)
+    }
+}
+
+// Wraps a Rust function as a Lox callable: the arity is checked and error
+// messages get the function name and call line. The function is also given
+// the runtime and the call line, to check the memory limit before allocating.
+pub fn native<F>(name: &str, arity: usize, function: F) -> Value
+where
+    F: Fn(&[Value], &Runtime, usize) -> Result<Value, Failure> + 'static,
+{
+    let name = name.to_string();
+
+    let callable = move |values: Vec<Value>, scope: &Scope, line: usize| {
+        check_arity(&values, arity, line)?;
+
+        function(&values, scope.runtime(), line)
+            .map(Flow::Next)
+            .map_err(|failure| match failure {
+                Failure::Message(message) => Error::runtime(format!("{name}: {message}"), line),
+                Failure::Error(error) => error,
+            })
+    };
+
+    Value::Callable(Rc::new(callable), None)
+}
+
+// Like `native`, for functions registered by an embedding application. Thrown
+// values and exits pass through untouched.
+pub fn host<F>(name: &str, arity: usize, function: F) -> Value
+where
+    F: Fn(&[Value]) -> Result<Value, Error> + 'static,
+{
+    let name = name.to_string();
+
+    let callable = move |values: Vec<Value>, _scope: &Scope, line: usize| {
+        check_arity(&values, arity, line)?;
+
+        function(&values)
+            .map(Flow::Next)
+            .map_err(|error| match error {
+                Error::Runtime { message, .. } => {
+                    Error::runtime(format!("{name}: {message}"), line)
+                }
+                error => error,
+            })
+    };
+
+    Value::Callable(Rc::new(callable), None)
+}
+
+pub fn define<F>(scope: &Scope, name: &str, arity: usize, function: F)
+where
+    F: Fn(&[Value]) -> Result<Value, String> + 'static,
+{
+    define_with_runtime(scope, name, arity, move |args, _runtime, _line| {
+        function(args).map_err(Failure::Message)
+    });
+}
+
+// Like `define`, for natives that touch the runtime or allocate in proportion
+// to their input
+pub fn define_with_runtime<F>(scope: &Scope, name: &str, arity: usize, function: F)
+where
+    F: Fn(&[Value], &Runtime, usize) -> Result<Value, Failure> + 'static,
+{
+    scope.define(name.to_string(), native(name, arity, function));
+}
+
+// `value` as printed, failing as soon as the text would go over the memory limit
+pub fn display(value: &Value, runtime: &Runtime, line: usize) -> Result<String, Error> {
+    struct Bounded<'a> {
+        string: String,
+        runtime: &'a Runtime,
+        line: usize,
+        error: Option<Error>,
+    }
+
+    impl fmt::Write for Bounded<'_> {
+        fn write_str(&mut self, text: &str) -> fmt::Result {
+            if let Err(error) = self.runtime.limits.reserve_growth(
+                (self.string.len(), self.string.capacity()),
+                text.len(),
+                self.line,
+            ) {
+                self.error = Some(error);
+                return Err(fmt::Error);
+            }
+
+            self.string.push_str(text);
+            Ok(())
+        }
+    }
+
+    let mut bounded = Bounded {
+        string: String::new(),
+        runtime,
+        line,
+        error: None,
+    };
+
+    match write!(bounded, "{value}") {
+        Ok(()) => Ok(bounded.string),
+        Err(_) => Err(bounded.error.expect("only the memory limit fails a write")),
+    }
+}
+
+pub fn number(args: &[Value], index: usize) -> Result<f64, String> {
+    match &args[index] {
+        Value::Literal(Literal::Number(number)) => Ok(*number),
+        value => Err(format!(
+            "Argument {} must be a number, got '{value}'.",
+            index + 1
+        )),
+    }
+}
+
+pub fn string(args: &[Value], index: usize) -> Result<&str, String> {
+    match &args[index] {
+        Value::Literal(Literal::String(string)) => Ok(string),
+        value => Err(format!(
+            "Argument {} must be a string, got '{value}'.",
+            index + 1
+        )),
+    }
+}
+
+pub fn integer(args: &[Value], index: usize) -> Result<usize, String> {
+    let number = number(args, index)?;
+
+    if number < 0.0 || number.fract() != 0.0 || !number.is_finite() {
+        return Err(format!(
+            "Argument {} must be a non-negative integer, got '{number}'.",
+            index + 1
+        ));
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    Ok(number as usize)
+}
+
+pub fn list(args: &[Value], index: usize) -> Result<&[Value], String> {
+    match &args[index] {
+        Value::List(values) => Ok(values),
+        value => Err(format!(
+            "Argument {} must be a list, got '{value}'.",
+            index + 1
+        )),
+    }
+}
+
+pub fn globals(scope: &Scope) {
+    time::globals(scope);
+    math::globals(scope);
+    string::globals(scope);
+    io::globals(scope);
+    error::globals(scope);
+    fs::globals(scope);
+    process::globals(scope);
+    random::globals(scope);
+    json::globals(scope);
+    collections::globals(scope);
+}
@@ -0,0 +1,38 @@
+use std::{env, rc::Rc};
+
+use super::{define, host, number, string, Error, Literal, Scope, Value};
+
+pub fn globals(scope: &Scope) {
+    let runtime = scope.runtime().clone();
+    define(scope, "args", 0, move |_args| {
+        let args = runtime
+            .options
+            .script_args
+            .iter()
+            .map(|arg| Value::Literal(Literal::String(arg.as_str().into())))
+            .collect();
+
+        Ok(Value::List(Rc::new(args)))
+    });
+
+    define(scope, "env", 1, |args| {
+        let value =
+            env::var(string(args, 0)?).map_or(Literal::Nil, |value| Literal::String(value.into()));
+
+        Ok(Value::Literal(value))
+    });
+
+    // Not a plain native since it unwinds with `Error::Exit` instead of a runtime error
+    let exit = host("exit", 1, |args| {
+        let code = number(args, 0).map_err(|message| Error::runtime(message, 0))?;
+
+        if code.fract() != 0.0 || code < f64::from(i32::MIN) || code > f64::from(i32::MAX) {
+            return Err(Error::runtime(format!("Invalid status code '{code}'."), 0));
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        Err(Error::Exit(code as i32))
+    });
+
+    scope.define(String::from("exit"), exit);
+}
@@ -0,0 +1,39 @@
+use super::{define, integer, number, Literal, Scope, Value};
+use crate::evaluate::prng::Prng;
+
+pub fn globals(scope: &Scope) {
+    let runtime = scope.runtime().clone();
+    define(scope, "random", 0, move |_args| {
+        let value = runtime.random.borrow_mut().next_f64();
+        Ok(Value::Literal(Literal::Number(value)))
+    });
+
+    let runtime = scope.runtime().clone();
+    define(scope, "random_int", 2, move |args| {
+        let (low, high) = (number(args, 0)?, number(args, 1)?);
+
+        if low.fract() != 0.0 || high.fract() != 0.0 || low > high {
+            return Err(format!(
+                "Bounds must be integers with low <= high, got '{low}' and '{high}'."
+            ));
+        }
+
+        // Past 2^53 not every integer is representable, so the result wouldn't be uniform
+        if high - low >= 9_007_199_254_740_992.0 {
+            return Err(String::from("Range between bounds is too large."));
+        }
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let span = (high - low) as u64 + 1;
+        let offset = runtime.random.borrow_mut().below(span);
+
+        #[allow(clippy::cast_precision_loss)]
+        Ok(Value::Literal(Literal::Number(low + offset as f64)))
+    });
+
+    let runtime = scope.runtime().clone();
+    define(scope, "seed", 1, move |args| {
+        *runtime.random.borrow_mut() = Prng::new(integer(args, 0)? as u64);
+        Ok(Value::Literal(Literal::Nil))
+    });
+}
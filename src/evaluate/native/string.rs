@@ -0,0 +1,181 @@
+use std::{mem, rc::Rc};
+
+use super::{define, define_with_runtime, display, integer, list, string, Literal, Scope, Value};
+
+fn string_value(string: impl Into<String>) -> Value {
+    Value::Literal(Literal::String(string.into().into()))
+}
+
+fn number_value(number: usize) -> Value {
+    #[allow(clippy::cast_precision_loss)]
+    Value::Literal(Literal::Number(number as f64))
+}
+
+fn bool_value(bool: bool) -> Value {
+    Value::Literal(Literal::Boolean(bool))
+}
+
+// Indexes are in Unicode scalar values, not bytes
+fn char_index(string: &str, needle: &str) -> Option<usize> {
+    let byte = string.find(needle)?;
+    Some(string[..byte].chars().count())
+}
+
+#[allow(clippy::too_many_lines)]
+pub fn globals(scope: &Scope) {
+    define(scope, "len", 1, |args| match &args[0] {
+        Value::Literal(Literal::String(string)) => Ok(number_value(string.chars().count())),
+        Value::List(values) => Ok(number_value(values.len())),
+        Value::Map(entries) => Ok(number_value(entries.len())),
+        value => Err(format!(
+            "Argument 1 must be a string, a list or a map, got '{value}'."
+        )),
+    });
+
+    define(scope, "substr", 3, |args| {
+        let string = string(args, 0)?;
+        let start = integer(args, 1)?;
+        let length = integer(args, 2)?;
+
+        if start > string.chars().count() {
+            return Err(format!("Start index {start} is out of range."));
+        }
+
+        Ok(string_value(
+            string.chars().skip(start).take(length).collect::<String>(),
+        ))
+    });
+
+    define(scope, "index_of", 2, |args| {
+        let index = char_index(string(args, 0)?, string(args, 1)?);
+
+        #[allow(clippy::cast_precision_loss)]
+        Ok(Value::Literal(Literal::Number(
+            index.map_or(-1.0, |index| index as f64),
+        )))
+    });
+
+    define_with_runtime(scope, "split", 2, |args, runtime, line| {
+        let text = string(args, 0)?;
+        let separator = string(args, 1)?;
+
+        let count = if separator.is_empty() {
+            text.chars().count()
+        } else {
+            text.matches(separator).count() + 1
+        };
+
+        // Every part is a value pointing at its own copy of the text
+        let part = mem::size_of::<Value>() + 2 * mem::size_of::<usize>();
+        runtime
+            .limits
+            .reserve(text.len().saturating_add(count.saturating_mul(part)), line)?;
+
+        let parts: Vec<Value> = if separator.is_empty() {
+            text.chars().map(string_value).collect()
+        } else {
+            text.split(separator).map(string_value).collect()
+        };
+
+        Ok(Value::List(Rc::new(parts)))
+    });
+
+    define_with_runtime(scope, "join", 2, |args, runtime, line| {
+        let values = list(args, 0)?;
+        let separator = string(args, 1)?;
+
+        let mut joined = String::new();
+
+        for (index, value) in values.iter().enumerate() {
+            let value = display(value, runtime, line)?;
+            let separator = if index > 0 { separator } else { "" };
+
+            runtime.limits.reserve_growth(
+                (joined.len(), joined.capacity()),
+                separator.len() + value.len(),
+                line,
+            )?;
+            joined.push_str(separator);
+            joined.push_str(&value);
+        }
+
+        Ok(string_value(joined))
+    });
+
+    define(scope, "upper", 1, |args| {
+        Ok(string_value(string(args, 0)?.to_uppercase()))
+    });
+    define(scope, "lower", 1, |args| {
+        Ok(string_value(string(args, 0)?.to_lowercase()))
+    });
+    define(scope, "trim", 1, |args| {
+        Ok(string_value(string(args, 0)?.trim()))
+    });
+
+    define_with_runtime(scope, "replace", 3, |args, runtime, line| {
+        let text = string(args, 0)?;
+        let from = string(args, 1)?;
+        let to = string(args, 2)?;
+
+        if from.is_empty() {
+            return Err(String::from("Search string must not be empty.").into());
+        }
+
+        let count = text.matches(from).count();
+        let size = (text.len() - count * from.len()).saturating_add(count.saturating_mul(to.len()));
+        runtime.limits.reserve(size, line)?;
+
+        Ok(string_value(text.replace(from, to)))
+    });
+
+    define(scope, "starts_with", 2, |args| {
+        Ok(bool_value(string(args, 0)?.starts_with(string(args, 1)?)))
+    });
+
+    define(scope, "ends_with", 2, |args| {
+        Ok(bool_value(string(args, 0)?.ends_with(string(args, 1)?)))
+    });
+
+    define(scope, "char_at", 2, |args| {
+        let index = integer(args, 1)?;
+
+        string(args, 0)?
+            .chars()
+            .nth(index)
+            .map(string_value)
+            .ok_or_else(|| format!("Index {index} is out of range."))
+    });
+
+    define(scope, "ord", 1, |args| {
+        let mut chars = string(args, 0)?.chars();
+
+        match (chars.next(), chars.next()) {
+            (Some(char), None) => Ok(Value::Literal(Literal::Number(f64::from(u32::from(char))))),
+            _ => Err(String::from("Argument 1 must be a single character.")),
+        }
+    });
+
+    define(scope, "chr", 1, |args| {
+        let code = integer(args, 0)?;
+
+        u32::try_from(code)
+            .ok()
+            .and_then(char::from_u32)
+            .map(string_value)
+            .ok_or_else(|| format!("{code} is not a valid code point."))
+    });
+
+    define_with_runtime(scope, "str", 1, |args, runtime, line| {
+        Ok(string_value(display(&args[0], runtime, line)?))
+    });
+
+    define(scope, "num", 1, |args| {
+        let string = string(args, 0)?;
+
+        string
+            .trim()
+            .parse::<f64>()
+            .map(|number| Value::Literal(Literal::Number(number)))
+            .map_err(|_| format!("Cannot convert '{string}' to a number."))
+    });
+}
@@ -0,0 +1,24 @@
+use super::{define, Literal, Scope, Value};
+
+pub fn globals(scope: &Scope) {
+    let runtime = scope.runtime().clone();
+    define(scope, "clock", 0, move |_args| {
+        Ok(Value::Literal(Literal::Number(runtime.clock.now())))
+    });
+
+    // Unlike `clock()` these count from interpreter start, which keeps full
+    // precision for timing short sections of code
+    let runtime = scope.runtime().clone();
+    define(scope, "clock_ms", 0, move |_args| {
+        Ok(Value::Literal(Literal::Number(
+            runtime.clock.elapsed() * 1e3,
+        )))
+    });
+
+    let runtime = scope.runtime().clone();
+    define(scope, "clock_ns", 0, move |_args| {
+        Ok(Value::Literal(Literal::Number(
+            (runtime.clock.elapsed() * 1e9).round(),
+        )))
+    });
+}
@@ -0,0 +1,220 @@
+use std::{mem, rc::Rc};
+
+use super::{evaluate, Literal, OptLevel, Scope, Value};
+use crate::parser::Expr;
+
+fn is_literal(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::True | Expr::False | Expr::Nil | Expr::String(_) | Expr::Number(_)
+    )
+}
+
+fn literal_expr(literal: Literal) -> Expr {
+    match literal {
+        Literal::Boolean(true) => Expr::True,
+        Literal::Boolean(false) => Expr::False,
+        Literal::Nil => Expr::Nil,
+        Literal::String(string) => Expr::String(string),
+        Literal::Number(number) => Expr::Number(number),
+    }
+}
+
+// Folding runs the evaluator itself, so results match what the unoptimized
+// program would compute; anything that errors is left for runtime to report
+fn fold(expr: Expr, scope: &Scope) -> Expr {
+    match evaluate(&expr, scope) {
+        Ok(Value::Literal(literal)) => literal_expr(literal),
+        _ => expr,
+    }
+}
+
+fn truthy(expr: &Expr, scope: &Scope) -> Option<bool> {
+    if !is_literal(expr) {
+        return None;
+    }
+
+    evaluate(expr, scope).ok().map(|value| value.as_bool())
+}
+
+// Reuses the existing allocation rather than boxing the result again
+fn boxed(mut expr: Box<Expr>, scope: &Scope) -> Box<Expr> {
+    *expr = optimize_expr(mem::replace(&mut *expr, Expr::Nil), scope);
+    expr
+}
+
+fn calls(args: Vec<Vec<Expr>>, scope: &Scope) -> Vec<Vec<Expr>> {
+    args.into_iter()
+        .map(|args| {
+            args.into_iter()
+                .map(|arg| optimize_expr(arg, scope))
+                .collect()
+        })
+        .collect()
+}
+
+// Nothing after a `return` or `throw` in the same block can run
+fn statements(exprs: Vec<Expr>, scope: &Scope) -> Vec<Expr> {
+    let mut optimized = vec![];
+
+    for expr in exprs {
+        let expr = optimize_expr(expr, scope);
+        let ends = matches!(expr, Expr::Return(_) | Expr::TailCall(..) | Expr::Throw(..));
+
+        // An empty statement only matters as the value of a block it ends
+        if let Some(Expr::Semicolon) = optimized.last() {
+            optimized.pop();
+        }
+
+        optimized.push(expr);
+
+        if ends {
+            break;
+        }
+    }
+
+    optimized
+}
+
+#[allow(clippy::too_many_lines)]
+fn optimize_expr(expr: Expr, scope: &Scope) -> Expr {
+    match expr {
+        Expr::Unary(operator, operand, line) => {
+            let operand = boxed(operand, scope);
+
+            if is_literal(&operand) {
+                return fold(Expr::Unary(operator, operand, line), scope);
+            }
+
+            Expr::Unary(operator, operand, line)
+        }
+        Expr::Binary(operator, left, right, line) => {
+            let (left, right) = (boxed(left, scope), boxed(right, scope));
+
+            if is_literal(&left) && is_literal(&right) {
+                return fold(Expr::Binary(operator, left, right, line), scope);
+            }
+
+            Expr::Binary(operator, left, right, line)
+        }
+        // Parentheses only matter to the parser
+        Expr::Grouping(expr) => optimize_expr(*expr, scope),
+        Expr::Interpolation(exprs, line) => {
+            let exprs: Vec<Expr> = exprs
+                .into_iter()
+                .map(|expr| optimize_expr(expr, scope))
+                .collect();
+
+            if exprs.iter().all(is_literal) {
+                return fold(Expr::Interpolation(exprs, line), scope);
+            }
+
+            Expr::Interpolation(exprs, line)
+        }
+        Expr::Or(left, right) => {
+            let (left, right) = (boxed(left, scope), boxed(right, scope));
+
+            match truthy(&left, scope) {
+                Some(true) => *left,
+                Some(false) => *right,
+                None => Expr::Or(left, right),
+            }
+        }
+        Expr::And(left, right) => {
+            let (left, right) = (boxed(left, scope), boxed(right, scope));
+
+            match truthy(&left, scope) {
+                Some(true) => *right,
+                Some(false) => *left,
+                None => Expr::And(left, right),
+            }
+        }
+        Expr::Ternary(condition, then, otherwise) => {
+            let condition = boxed(condition, scope);
+            let (then, otherwise) = (boxed(then, scope), boxed(otherwise, scope));
+
+            match truthy(&condition, scope) {
+                Some(true) => *then,
+                Some(false) => *otherwise,
+                None => Expr::Ternary(condition, then, otherwise),
+            }
+        }
+        Expr::IfElse(condition, then, otherwise) => {
+            let condition = boxed(condition, scope);
+            let then = boxed(then, scope);
+            let otherwise = otherwise.map(|otherwise| boxed(otherwise, scope));
+
+            match truthy(&condition, scope) {
+                Some(true) => *then,
+                Some(false) => otherwise.map_or(Expr::Nil, |otherwise| *otherwise),
+                None => Expr::IfElse(condition, then, otherwise),
+            }
+        }
+        Expr::While(condition, body, line) => {
+            let condition = boxed(condition, scope);
+
+            if truthy(&condition, scope) == Some(false) {
+                return Expr::Nil;
+            }
+
+            Expr::While(condition, boxed(body, scope), line)
+        }
+        Expr::For(init, condition, increment, body, line) => {
+            let init = init.map(|init| boxed(init, scope));
+            let condition = condition.map(|condition| boxed(condition, scope));
+
+            if matches!(&condition, Some(condition) if truthy(condition, scope) == Some(false)) {
+                return init.map_or(Expr::Nil, |init| *init);
+            }
+
+            let increment = increment.map(|increment| boxed(increment, scope));
+
+            Expr::For(init, condition, increment, boxed(body, scope), line)
+        }
+        Expr::Statements(exprs, locals) => Expr::Statements(statements(exprs, scope), locals),
+        Expr::Assignment(name, slot, value, define, line) => {
+            Expr::Assignment(name, slot, boxed(value, scope), define, line)
+        }
+        Expr::Postfix(name, slot, value, line) => {
+            Expr::Postfix(name, slot, boxed(value, scope), line)
+        }
+        Expr::Print(expr, line) => Expr::Print(boxed(expr, scope), line),
+        Expr::Callable(name, slot, args, line) => {
+            Expr::Callable(name, slot, calls(args, scope), line)
+        }
+        Expr::TailCall(name, slot, args, line) => {
+            Expr::TailCall(name, slot, calls(args, scope), line)
+        }
+        Expr::Fun(name, slot, args, body, locals) => {
+            let body = optimize_expr(Rc::unwrap_or_clone(body), scope);
+            Expr::Fun(name, slot, args, Rc::new(body), locals)
+        }
+        Expr::Return(expr) => Expr::Return(boxed(expr, scope)),
+        Expr::Throw(expr, line) => Expr::Throw(boxed(expr, scope), line),
+        Expr::Try(body, catch, finally) => Expr::Try(
+            boxed(body, scope),
+            catch.map(|(name, handler)| (name, boxed(handler, scope))),
+            finally.map(|finally| boxed(finally, scope)),
+        ),
+        expr @ (Expr::True
+        | Expr::False
+        | Expr::Nil
+        | Expr::String(_)
+        | Expr::Number(_)
+        | Expr::Identifier(..)
+        | Expr::Semicolon
+        | Expr::Import(..)) => expr,
+    }
+}
+
+// Folds constant expressions and drops code that can never run
+pub fn optimize(exprs: Vec<Expr>, level: OptLevel) -> Vec<Expr> {
+    if level == OptLevel::O0 {
+        return exprs;
+    }
+
+    // Only ever evaluates literals, so nothing needs to be defined in it
+    let scope = Scope::new(Rc::default());
+
+    statements(exprs, &scope)
+}
@@ -0,0 +1,31 @@
+use core::fmt;
+use std::io::{self, BufWriter, Write};
+
+// Where `print` writes to: buffered stdout unless an embedder swaps it out
+pub struct Output(Box<dyn Write>);
+
+impl Default for Output {
+    fn default() -> Self {
+        Self(Box::new(BufWriter::new(io::stdout())))
+    }
+}
+
+impl fmt::Debug for Output {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "Output")
+    }
+}
+
+impl Output {
+    pub fn new(writer: impl Write + 'static) -> Self {
+        Self(Box::new(writer))
+    }
+
+    pub fn write(&mut self, text: &str) -> io::Result<()> {
+        self.0.write_all(text.as_bytes())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
@@ -0,0 +1,182 @@
+use std::rc::Rc;
+
+use crate::parser::{Expr, Slot};
+
+fn declare(names: &mut Vec<String>, name: &str) {
+    if !names.iter().any(|declared| declared == name) {
+        names.push(name.to_string());
+    }
+}
+
+// Variables a statement declares in the scope it runs in; nested blocks and
+// functions get scopes of their own
+fn declarations(expr: &Expr, names: &mut Vec<String>) {
+    match expr {
+        Expr::Assignment(name, _, _, true, _) | Expr::Fun(name, ..) => declare(names, name),
+        Expr::Import(_, Some(imports), _) => {
+            for name in imports {
+                declare(names, name);
+            }
+        }
+        Expr::IfElse(_, then, otherwise) => {
+            declarations(then, names);
+
+            if let Some(otherwise) = otherwise {
+                declarations(otherwise, names);
+            }
+        }
+        Expr::While(_, body, _) => declarations(body, names),
+        Expr::For(init, _, _, body, _) => {
+            if let Some(init) = init {
+                declarations(init, names);
+            }
+
+            declarations(body, names);
+        }
+        _ => {}
+    }
+}
+
+// Innermost scope last; globals are never in here and stay looked up by name
+#[derive(Default)]
+struct Resolver {
+    scopes: Vec<Vec<String>>,
+}
+
+impl Resolver {
+    // Every declaration of a scope is known before its statements are resolved,
+    // so functions can refer to locals declared after them
+    fn slot(&self, name: &str) -> Option<Slot> {
+        self.scopes
+            .iter()
+            .rev()
+            .enumerate()
+            .find_map(|(depth, names)| {
+                let index = names.iter().position(|local| local == name)?;
+                Some(Slot { depth, index })
+            })
+    }
+
+    fn statements(&mut self, exprs: &mut [Expr]) {
+        for expr in exprs {
+            self.resolve(expr);
+        }
+    }
+
+    fn calls(&mut self, args: &mut [Vec<Expr>]) {
+        for args in args {
+            self.statements(args);
+        }
+    }
+
+    fn resolve(&mut self, expr: &mut Expr) {
+        match expr {
+            Expr::Identifier(name, slot, _) => *slot = self.slot(name),
+            Expr::Assignment(name, slot, value, _, _) | Expr::Postfix(name, slot, value, _) => {
+                self.resolve(value);
+                *slot = self.slot(name);
+            }
+            Expr::Callable(name, slot, args, _) | Expr::TailCall(name, slot, args, _) => {
+                *slot = self.slot(name);
+                self.calls(args);
+            }
+            Expr::Statements(exprs, locals) => {
+                let mut names = vec![];
+
+                for expr in exprs.iter() {
+                    declarations(expr, &mut names);
+                }
+
+                if names.is_empty() {
+                    self.statements(exprs);
+                    return;
+                }
+
+                self.scopes.push(names);
+                self.statements(exprs);
+                *locals = self.scopes.pop().unwrap_or_default().into();
+            }
+            // Parameters and the body's own declarations share the call's scope
+            Expr::Fun(name, slot, params, body, locals) => {
+                *slot = self.slot(name);
+
+                let body = Rc::get_mut(body).expect("functions are resolved before they run");
+                let mut names = params.clone();
+
+                if let Expr::Statements(exprs, _) = body {
+                    for expr in exprs.iter() {
+                        declarations(expr, &mut names);
+                    }
+
+                    self.scopes.push(names);
+                    self.statements(exprs);
+                } else {
+                    self.scopes.push(names);
+                    self.resolve(body);
+                }
+
+                *locals = self.scopes.pop().unwrap_or_default().into();
+            }
+            Expr::Try(body, catch, finally) => {
+                self.resolve(body);
+
+                if let Some((name, handler)) = catch {
+                    self.scopes.push(vec![name.clone()]);
+                    self.resolve(handler);
+                    self.scopes.pop();
+                }
+
+                if let Some(finally) = finally {
+                    self.resolve(finally);
+                }
+            }
+            Expr::Interpolation(exprs, _) => self.statements(exprs),
+            Expr::Unary(_, expr, _)
+            | Expr::Grouping(expr)
+            | Expr::Print(expr, _)
+            | Expr::Return(expr)
+            | Expr::Throw(expr, _) => self.resolve(expr),
+            Expr::Binary(_, left, right, _)
+            | Expr::Or(left, right)
+            | Expr::And(left, right)
+            | Expr::While(left, right, _) => {
+                self.resolve(left);
+                self.resolve(right);
+            }
+            Expr::Ternary(condition, then, otherwise) => {
+                self.resolve(condition);
+                self.resolve(then);
+                self.resolve(otherwise);
+            }
+            Expr::IfElse(condition, then, otherwise) => {
+                self.resolve(condition);
+                self.resolve(then);
+
+                if let Some(otherwise) = otherwise {
+                    self.resolve(otherwise);
+                }
+            }
+            Expr::For(init, condition, increment, body, _) => {
+                for expr in [init, condition, increment].into_iter().flatten() {
+                    self.resolve(expr);
+                }
+
+                self.resolve(body);
+            }
+            Expr::True
+            | Expr::False
+            | Expr::Nil
+            | Expr::String(_)
+            | Expr::Number(_)
+            | Expr::Semicolon
+            | Expr::Import(..) => {}
+        }
+    }
+}
+
+// Gives every local variable a slot in the scope that declares it, so running
+// the program needs no lookups by name for them
+pub fn resolve(mut exprs: Vec<Expr>) -> Vec<Expr> {
+    Resolver::default().statements(&mut exprs);
+    exprs
+}
@@ -0,0 +1,129 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    io::{self, Write},
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::{
+    clock::{Clock, FakeClock},
+    limits::Limits,
+    output::Output,
+    prng::Prng,
+    Value,
+};
+
+pub type Exports = HashMap<String, Value>;
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OptLevel {
+    // Run the syntax tree exactly as parsed, for debugging
+    O0,
+    // Fold constant expressions and drop unreachable code before running
+    #[default]
+    O1,
+}
+
+// Settings chosen by whoever starts the interpreter, e.g. from command-line flags
+#[derive(Default, Debug)]
+pub struct Options {
+    // Directories the file natives may touch; empty denies all file access
+    pub fs_roots: Vec<PathBuf>,
+    // Command-line arguments following the script path
+    pub script_args: Vec<String>,
+    // Seed for the random natives; taken from the system clock when unset
+    pub seed: Option<u64>,
+    // Deterministic replacement for the clock natives, for golden tests
+    pub fake_clock: Option<FakeClock>,
+    // Loop iterations plus function calls allowed per run; unlimited when unset
+    pub max_steps: Option<u64>,
+    // Call depth at which "Stack overflow." is raised; unlimited when unset
+    pub max_depth: Option<usize>,
+    // Native stack bytes a run may use before "Stack overflow." is raised,
+    // `DEFAULT_MAX_STACK` when unset
+    pub max_stack: Option<usize>,
+    // Wall-clock time allowed per run; unlimited when unset
+    pub timeout: Option<Duration>,
+    // Heap bytes a run may allocate on top of what was live when it started;
+    // needs `CountingAllocator` installed as the global allocator
+    pub max_memory: Option<usize>,
+    pub opt_level: OptLevel,
+}
+
+// State shared by every scope of one interpreter run
+#[derive(Default, Debug)]
+pub struct Runtime {
+    pub options: Options,
+    pub random: RefCell<Prng>,
+    pub clock: Clock,
+    pub limits: Limits,
+    output: RefCell<Output>,
+    modules: RefCell<HashMap<PathBuf, Exports>>,
+    // Files currently being evaluated, innermost last
+    imports: RefCell<Vec<PathBuf>>,
+}
+
+impl Runtime {
+    pub fn new(options: Options) -> Self {
+        let seed = options.seed.unwrap_or_else(|| {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+
+            #[allow(clippy::cast_possible_truncation)]
+            let nanos = now.as_nanos() as u64;
+            nanos
+        });
+
+        Self {
+            random: RefCell::new(Prng::new(seed)),
+            clock: Clock::new(options.fake_clock),
+            limits: Limits::new(&options),
+            options,
+            ..Self::default()
+        }
+    }
+
+    // Pending output goes to the old sink before switching
+    pub fn set_output(&self, writer: impl Write + 'static) -> io::Result<()> {
+        let mut output = self.output.borrow_mut();
+        output.flush()?;
+        *output = Output::new(writer);
+
+        Ok(())
+    }
+
+    pub fn write(&self, text: &str) -> io::Result<()> {
+        self.output.borrow_mut().write(text)
+    }
+
+    pub fn flush(&self) -> io::Result<()> {
+        self.output.borrow_mut().flush()
+    }
+
+    pub fn enter(&self, path: PathBuf) {
+        self.imports.borrow_mut().push(path);
+    }
+
+    pub fn leave(&self) {
+        self.imports.borrow_mut().pop();
+    }
+
+    pub fn current_file(&self) -> Option<PathBuf> {
+        self.imports.borrow().last().cloned()
+    }
+
+    pub fn import_chain(&self, path: &PathBuf) -> Option<Vec<PathBuf>> {
+        let imports = self.imports.borrow();
+        let start = imports.iter().position(|import| import == path)?;
+
+        Some(imports[start..].to_vec())
+    }
+
+    pub fn module(&self, path: &PathBuf) -> Option<Exports> {
+        self.modules.borrow().get(path).cloned()
+    }
+
+    pub fn cache_module(&self, path: PathBuf, exports: Exports) {
+        self.modules.borrow_mut().insert(path, exports);
+    }
+}
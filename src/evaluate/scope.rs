@@ -1,75 +1,191 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc, time::SystemTime};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    hash::{BuildHasherDefault, Hasher},
+    rc::Rc,
+};
 
-use super::{value::Value, Expr};
+use super::{native, runtime::Runtime, value::Value, Literal};
+use crate::parser::{Locals, Slot};
 
-#[allow(clippy::needless_pass_by_value)]
-fn clock(_args: Vec<Expr>, _scope: Scope, _args_scope: Scope) -> Value {
-    let duration = SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .unwrap();
+// Globals are looked up by name on every use; identifiers are short and
+// come from the script itself, so SipHash's collision resistance isn't needed
+#[derive(Default)]
+struct NameHasher(u64);
 
-    Value::Literal(super::Literal::Number(duration.as_secs_f64().floor()))
+impl Hasher for NameHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0.rotate_left(5) ^ u64::from(byte)).wrapping_mul(0x517c_c1b7_2722_0a95);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+type Globals = HashMap<String, Value, BuildHasherDefault<NameHasher>>;
+
+// Variables of one block or function call, stored by the slot the resolver
+// gave them; a slot stays empty until its declaration has run
+#[derive(Debug)]
+struct Env {
+    prev: Option<Rc<Env>>,
+    names: Locals,
+    slots: RefCell<Vec<Option<Value>>>,
+}
+
+impl Env {
+    fn position(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|local| local == name)
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct Scope {
-    pub prev: Option<Rc<RefCell<Scope>>>,
-    variables: Rc<RefCell<HashMap<String, Value>>>,
+    globals: Rc<RefCell<Globals>>,
+    locals: Option<Rc<Env>>,
+    runtime: Rc<Runtime>,
 }
 
 impl Scope {
-    pub fn new(variables: HashMap<String, Value>, prev: Option<Rc<RefCell<Scope>>>) -> Self {
-        Self { prev, variables: Rc::new(RefCell::new(variables)) }
+    pub fn new(runtime: Rc<Runtime>) -> Self {
+        Self {
+            globals: Rc::default(),
+            locals: None,
+            runtime,
+        }
     }
 
-    pub fn global() -> Self {
-        let scope = Scope::new(HashMap::new(), None);
-        scope.define(String::from("clock"), Value::Callable(Rc::new(clock), None));
+    // Top-level scope of a script or imported module, sharing the runtime of the importer
+    pub fn module(runtime: Rc<Runtime>) -> Self {
+        let scope = Self::new(runtime);
+
+        native::globals(&scope);
         scope
     }
 
-    //pub fn push(&self) {
-    //    let mut stack = self.stack.borrow_mut();
-    //    stack.push(HashMap::new());
-    //}
-    //
-    //pub fn pop(&self) {
-    //    let mut stack = self.stack.borrow_mut();
-    //    stack.pop();
-    //}
-    //
-    //pub fn clear(&self) {
-    //    let mut stack = self.stack.borrow_mut();
-    //    stack.clear();
-    //}
+    pub fn runtime(&self) -> &Rc<Runtime> {
+        &self.runtime
+    }
+
+    pub fn block(&self, names: &Locals) -> Self {
+        self.frame(names, Vec::new())
+    }
+
+    // A scope whose first slots are filled with `values`, e.g. a call's arguments
+    pub fn frame(&self, names: &Locals, values: Vec<Value>) -> Self {
+        let mut slots: Vec<Option<Value>> = values.into_iter().map(Some).collect();
+        slots.resize(names.len(), None);
+
+        Self {
+            globals: self.globals.clone(),
+            locals: Some(Rc::new(Env {
+                prev: self.locals.clone(),
+                names: names.clone(),
+                slots: RefCell::new(slots),
+            })),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    fn env(&self, depth: usize) -> &Rc<Env> {
+        let mut env = self.locals.as_ref().expect("resolved slots have a scope");
+
+        for _ in 0..depth {
+            env = env.prev.as_ref().expect("resolved slots have a scope");
+        }
+
+        env
+    }
 
+    // By name, for natives, imports and the embedding API
     pub fn define(&self, name: String, value: Value) {
-        self.variables.borrow_mut().insert(name, value);
+        if let Some(env) = &self.locals {
+            if let Some(index) = env.position(&name) {
+                env.slots.borrow_mut()[index] = Some(value);
+                return;
+            }
+        }
+
+        self.globals.borrow_mut().insert(name, value);
+    }
+
+    pub fn set(&self, name: &str, value: Value) -> bool {
+        let mut locals = self.locals.as_ref();
+
+        while let Some(env) = locals {
+            if let Some(index) = env.position(name) {
+                if let Some(local) = &mut env.slots.borrow_mut()[index] {
+                    *local = value;
+                    return true;
+                }
+            }
+
+            locals = env.prev.as_ref();
+        }
+
+        if let Some(global) = self.globals.borrow_mut().get_mut(name) {
+            *global = value;
+            return true;
+        }
+
+        false
     }
 
-    pub fn set(&self, name: &str, value: Value) {
-        let mut variables = self.variables.borrow_mut();
-        if variables.contains_key(name) {
-            variables.insert(name.to_string(), value);
-            return;
+    pub fn get(&self, name: &str) -> Option<Value> {
+        let mut locals = self.locals.as_ref();
+
+        while let Some(env) = locals {
+            if let Some(index) = env.position(name) {
+                if let Some(value) = &env.slots.borrow()[index] {
+                    return Some(value.clone());
+                }
+            }
+
+            locals = env.prev.as_ref();
         }
 
-        if let Some(scope) = &self.prev {
-            return scope.borrow_mut().set(name, value);
+        self.globals.borrow().get(name).cloned()
+    }
+
+    pub fn declare(&self, name: &str, slot: Option<Slot>, value: Value) {
+        match slot {
+            Some(slot) => self.env(slot.depth).slots.borrow_mut()[slot.index] = Some(value),
+            None => self.define(name.to_string(), value),
         }
     }
 
-    pub fn get(&self, name: &str) -> Value {
-        let variables = self.variables.borrow();
-        if variables.contains_key(name) {
-            return variables[name].clone();
+    // A resolved slot that is still empty, e.g. a local used before its
+    // declaration, falls back to looking the name up like before resolving
+    pub fn assign(&self, name: &str, slot: Option<Slot>, value: Value) -> bool {
+        if let Some(slot) = slot {
+            if let Some(local) = &mut self.env(slot.depth).slots.borrow_mut()[slot.index] {
+                *local = value;
+                return true;
+            }
         }
 
-        if let Some(scope) = &self.prev {
-            return scope.borrow().get(name);
+        self.set(name, value)
+    }
+
+    pub fn lookup(&self, name: &str, slot: Option<Slot>) -> Option<Value> {
+        let Some(slot) = slot else {
+            return self.globals.borrow().get(name).cloned();
+        };
+
+        if let Some(value) = &self.env(slot.depth).slots.borrow()[slot.index] {
+            return Some(value.clone());
         }
 
-        eprintln!("Couldn't find scope value '{name}'");
-        std::process::exit(70);
+        self.get(name)
+    }
+
+    pub fn number(&self, slot: Slot) -> Option<f64> {
+        match &self.env(slot.depth).slots.borrow()[slot.index] {
+            Some(Value::Literal(Literal::Number(number))) => Some(*number),
+            _ => None,
+        }
     }
 }
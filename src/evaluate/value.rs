@@ -1,23 +1,89 @@
 use core::fmt;
-use std::rc::Rc;
+use std::{collections::BTreeMap, rc::Rc};
 
-use super::{Expr, Literal, Scope};
+use super::{Error, Literal, Scope};
 
-pub type Callable = Rc<dyn Fn(Vec<Expr>, Scope, Scope) -> Value>;
+// Receives evaluated arguments, the scope the function closes over and the call line
+pub type Callable = Rc<dyn Fn(Vec<Value>, &Scope, usize) -> Result<Flow, Error>>;
+
+pub struct TailCall {
+    pub callee: Value,
+    pub args: Vec<Value>,
+    pub line: usize,
+}
+
+// Where running a statement leads: on to the next one with its value, or out
+// of the enclosing function. Never seen outside the evaluator.
+pub enum Flow {
+    Next(Value),
+    Return(Value),
+    // Only ever returned by a function body, see `evaluate::call`
+    TailCall(Box<TailCall>),
+}
 
 #[derive(Clone)]
 pub enum Value {
     Literal(Literal),
     Callable(Callable, Option<Scope>),
-    Return(Box<Value>),
+    Error(Rc<Error>),
+    List(Rc<Vec<Value>>),
+    // Keys are kept sorted so iteration and printing are deterministic
+    Map(Rc<BTreeMap<String, Value>>),
+}
+
+impl Value {
+    // Equality for anything other than two literals: functions, lists, maps
+    // and caught errors are only equal to themselves
+    pub fn is(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Callable(left, _), Value::Callable(right, _)) => Rc::ptr_eq(left, right),
+            (Value::Error(left), Value::Error(right)) => Rc::ptr_eq(left, right),
+            (Value::List(left), Value::List(right)) => Rc::ptr_eq(left, right),
+            (Value::Map(left), Value::Map(right)) => Rc::ptr_eq(left, right),
+            _ => false,
+        }
+    }
+
+    pub fn as_bool(&self) -> bool {
+        match self {
+            Value::Literal(literal) => literal.as_bool(),
+            Value::Callable(_, _) | Value::Error(_) | Value::List(_) | Value::Map(_) => true,
+        }
+    }
 }
 
 impl fmt::Display for Value {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Value::Literal(literal) => write!(fmt, "{literal}"),
-            Value::Return(value) => write!(fmt, "{value}"),
             Value::Callable(_, _) => write!(fmt, "<fn foo>"),
+            Value::Error(error) => write!(fmt, "{error}"),
+            Value::List(values) => {
+                write!(fmt, "[")?;
+
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        write!(fmt, ", ")?;
+                    }
+
+                    write!(fmt, "{value}")?;
+                }
+
+                write!(fmt, "]")
+            }
+            Value::Map(entries) => {
+                write!(fmt, "{{")?;
+
+                for (index, (key, value)) in entries.iter().enumerate() {
+                    if index > 0 {
+                        write!(fmt, ", ")?;
+                    }
+
+                    write!(fmt, "{key}: {value}")?;
+                }
+
+                write!(fmt, "}}")
+            }
         }
     }
 }
@@ -26,8 +92,9 @@ impl fmt::Debug for Value {
     fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Value::Literal(literal) => write!(fmt, "{literal}"),
-            Value::Return(literal) => write!(fmt, "{literal}"),
             Value::Callable(_, _) => write!(fmt, "<fn foo>"),
+            Value::Error(error) => write!(fmt, "{error}"),
+            Value::List(_) | Value::Map(_) => write!(fmt, "{self}"),
         }
     }
 }
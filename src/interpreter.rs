@@ -0,0 +1,233 @@
+use core::fmt;
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+    rc::Rc,
+};
+
+use crate::{
+    evaluate::{self, Runtime, Scope, Value},
+    parser::{self, Expr},
+    scanner::{self, ScanTokens},
+};
+
+const TRACE_ENDS: usize = 10;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(PathBuf, io::Error),
+    Scan(Vec<scanner::Error>),
+    Parse(parser::Error),
+    Runtime(evaluate::Error),
+    Output(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(path, error) => {
+                write!(fmt, "Failed to read file {}: {error}", path.display())
+            }
+            Error::Scan(errors) => {
+                for (index, error) in errors.iter().enumerate() {
+                    if index > 0 {
+                        writeln!(fmt)?;
+                    }
+
+                    write!(fmt, "[line {}] Error: {}", error.line, error.message)?;
+                }
+
+                Ok(())
+            }
+            Error::Parse(error) => write!(fmt, "{error}"),
+            Error::Runtime(error @ evaluate::Error::Exit(_)) => write!(fmt, "{error}"),
+            Error::Runtime(error) => {
+                write!(fmt, "{error}")?;
+
+                // Very deep traces, e.g. from a stack overflow, keep only both ends
+                let trace = error.stack_trace();
+                let skipped = trace.len().saturating_sub(2 * TRACE_ENDS);
+
+                for (index, line) in trace.iter().enumerate() {
+                    if skipped > 0 && index == TRACE_ENDS {
+                        write!(fmt, "\n... {skipped} more calls ...")?;
+                    }
+
+                    if skipped == 0 || index < TRACE_ENDS || index >= trace.len() - TRACE_ENDS {
+                        write!(fmt, "\n{line}")?;
+                    }
+                }
+
+                Ok(())
+            }
+            Error::Output(error) => write!(fmt, "Could not write output: {error}."),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+fn scan(source: &str) -> Result<ScanTokens, Error> {
+    let scan_tokens = scanner::scan_tokens(source);
+
+    if !scan_tokens.errors.is_empty() {
+        return Err(Error::Scan(scan_tokens.errors));
+    }
+
+    Ok(scan_tokens)
+}
+
+fn statements(source: &str) -> Result<Vec<Expr>, Error> {
+    let scan_tokens = scan(source)?;
+    parser::parse_statements(&scan_tokens.tokens).map_err(Error::Parse)
+}
+
+// One line per token in `TYPE lexeme literal` form, ending with EOF, along with
+// any scan errors; tokens before and after an error are still listed
+pub fn tokenize(source: &str) -> (Vec<String>, Vec<scanner::Error>) {
+    let scan_tokens = scanner::scan_tokens(source);
+
+    let mut lines: Vec<String> = scan_tokens
+        .tokens
+        .into_iter()
+        .map(|token| {
+            let value = token.literal.unwrap_or("null".to_string());
+            format!("{} {} {}", token.ty, token.lexeme, value)
+        })
+        .collect();
+
+    lines.push("EOF  null".to_string());
+
+    (lines, scan_tokens.errors)
+}
+
+// The syntax tree of `source` as an S-expression
+pub fn parse(source: &str) -> Result<String, Error> {
+    let scan_tokens = scan(source)?;
+    let tree = parser::parse_tokens(&scan_tokens.tokens).map_err(Error::Parse)?;
+
+    Ok(tree.to_string())
+}
+
+// An interpreter whose globals persist across calls, for embedding in other programs
+pub struct Interpreter {
+    scope: Scope,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new(evaluate::Options::default())
+    }
+}
+
+impl Interpreter {
+    pub fn new(options: evaluate::Options) -> Self {
+        Self {
+            scope: Scope::module(Rc::new(Runtime::new(options))),
+        }
+    }
+
+    // Replaces where `print` writes to, stdout by default
+    pub fn set_output(&self, writer: impl Write + 'static) -> Result<(), Error> {
+        self.scope
+            .runtime()
+            .set_output(writer)
+            .map_err(Error::Output)
+    }
+
+    // Runs `source` at the top level and returns the value of its last
+    // statement. Output is flushed whether or not it succeeds.
+    pub fn eval_str(&self, source: &str) -> Result<Value, Error> {
+        let result = self.eval_statements(source);
+        let flushed = self.scope.runtime().flush().map_err(Error::Output);
+
+        let value = result?;
+        flushed?;
+
+        Ok(value)
+    }
+
+    fn eval_statements(&self, source: &str) -> Result<Value, Error> {
+        self.scope
+            .runtime()
+            .limits
+            .start()
+            .map_err(Error::Runtime)?;
+
+        let mut value = Value::Literal(evaluate::Literal::Nil);
+
+        let runtime = self.scope.runtime();
+        let statements = evaluate::optimize(statements(source)?, runtime.options.opt_level);
+        let statements = evaluate::resolve(statements);
+
+        for expr in statements {
+            match evaluate::execute(&expr, &self.scope).map_err(Error::Runtime)? {
+                evaluate::Flow::Next(next) => value = next,
+                flow => return evaluate::finish(flow, &self.scope).map_err(Error::Runtime),
+            }
+        }
+
+        Ok(value)
+    }
+
+    // Like `eval_str`, but imports inside the file resolve relative to it
+    pub fn run_file(&self, path: impl Into<PathBuf>) -> Result<Value, Error> {
+        let path = path.into();
+        let source = fs::read_to_string(&path).map_err(|error| Error::Io(path.clone(), error))?;
+        let runtime = self.scope.runtime();
+        let entered = fs::canonicalize(&path)
+            .map(|path| runtime.enter(path))
+            .is_ok();
+
+        let result = self.eval_str(&source);
+
+        if entered {
+            runtime.leave();
+        }
+
+        result
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<Value> {
+        self.scope.get(name)
+    }
+
+    pub fn set_global(&self, name: &str, value: Value) {
+        self.scope.define(name.to_string(), value);
+    }
+
+    // Calls a function defined by a script or registered by the host, without
+    // going back through the parser. Output is flushed as with `eval_str`.
+    pub fn call_function(&self, name: &str, args: Vec<Value>) -> Result<Value, Error> {
+        let result = self.call(name, args);
+        let flushed = self.scope.runtime().flush().map_err(Error::Output);
+
+        let value = result?;
+        flushed?;
+
+        Ok(value)
+    }
+
+    fn call(&self, name: &str, args: Vec<Value>) -> Result<Value, Error> {
+        let function = self
+            .get_global(name)
+            .ok_or_else(|| Error::Runtime(evaluate::undefined_variable(name, 0)))?;
+
+        self.scope
+            .runtime()
+            .limits
+            .start()
+            .map_err(Error::Runtime)?;
+        evaluate::call(&function, args, &self.scope, 0).map_err(Error::Runtime)
+    }
+
+    // Makes a Rust function callable from scripts as a global; it can capture
+    // host state and is checked for `arity` like any other function
+    pub fn register_fn<F>(&self, name: &str, arity: usize, function: F)
+    where
+        F: Fn(&[Value]) -> Result<Value, evaluate::Error> + 'static,
+    {
+        self.set_global(name, evaluate::host(name, arity, function));
+    }
+}
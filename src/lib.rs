@@ -0,0 +1,15 @@
+mod evaluate;
+mod interpreter;
+mod memory;
+mod parser;
+mod scanner;
+mod utils;
+
+pub use evaluate::{
+    Error as RuntimeError, FakeClock, FromValue, Literal, OptLevel, Options, Value,
+    DEFAULT_MAX_STACK,
+};
+pub use interpreter::{parse, tokenize, Error, Interpreter};
+pub use memory::CountingAllocator;
+pub use parser::Error as ParseError;
+pub use scanner::Error as ScanError;
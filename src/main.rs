@@ -1,74 +1,109 @@
-use std::collections::HashMap;
 use std::env;
 use std::fs;
+use std::thread;
 
-use evaluate::Scope;
+use interpreter_starter_rust::{CountingAllocator, Error, Interpreter, ParseError, RuntimeError};
 
-mod evaluate;
-mod parser;
-mod scanner;
-mod utils;
+mod cli;
+
+fn exit_with(error: &Error) -> ! {
+    let status = match error {
+        Error::Io(_, _) => 66,
+        Error::Scan(_) | Error::Parse(ParseError::Syntax(_)) => 65,
+        Error::Parse(ParseError::Runtime(_)) => 70,
+        Error::Runtime(RuntimeError::Exit(code)) => std::process::exit(*code),
+        Error::Runtime(_) => 70,
+        Error::Output(_) => 74,
+    };
+
+    eprintln!("{error}");
+    std::process::exit(status);
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+// Only touched pages are actually allocated
+const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+// Left unused by scripts for the Lox call in progress when the stack limit is
+// checked, which takes a few hundred KiB in debug builds
+const STACK_HEADROOM: usize = 16 * 1024 * 1024;
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 3 {
-        eprintln!("Usage: {} tokenize <filename>", args[0]);
-        return;
+    let interpreter = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(run)
+        .expect("Failed to start interpreter thread");
+
+    if interpreter.join().is_err() {
+        std::process::exit(101);
     }
+}
 
-    let command = &args[1];
-    let filename = &args[2];
+fn run() {
+    let argv: Vec<String> = env::args().collect();
+    if argv.len() < 3 {
+        eprintln!("Usage: {} tokenize <filename>", argv[0]);
+        return;
+    }
 
-    let file_contents = fs::read_to_string(filename).unwrap_or_else(|_| {
-        eprintln!("Failed to read file {filename}");
-        String::new()
+    let mut args = cli::parse(&argv).unwrap_or_else(|error| {
+        eprintln!("{error}");
+        eprintln!(
+            "Usage: {} <command> [--allow-fs=DIR]... [--seed=N] [--fake-clock=START[:STEP]] \
+             [--max-steps=N] [--max-depth=N] [--timeout=MS] [--max-memory=BYTES] \
+             [-O0|-O1] <filename> [args]...",
+            argv[0]
+        );
+        std::process::exit(64);
     });
 
+    args.options
+        .max_stack
+        .get_or_insert(STACK_SIZE - STACK_HEADROOM);
+
+    let command = &args.command;
+    let filename = &args.filename;
+
+    let file_contents = || {
+        fs::read_to_string(filename).unwrap_or_else(|_| {
+            eprintln!("Failed to read file {filename}");
+            String::new()
+        })
+    };
+
     match command.as_str() {
         "tokenize" => {
             // You can use print statements as follows for debugging, they'll be visible when running tests.
             eprintln!("Logs from your program will appear here!");
 
-            let scan_tokens = scanner::scan_tokens(&file_contents);
+            let (tokens, errors) = interpreter_starter_rust::tokenize(&file_contents());
 
-            for error in &scan_tokens.errors {
+            for error in &errors {
                 eprintln!("[line {}] Error: {}", error.line, error.message);
             }
 
-            for token in scan_tokens.tokens {
-                let value = token.literal.unwrap_or("null".to_string());
-                println!("{} {} {}", token.ty, token.lexeme, value);
-            }
-
-            println!("EOF  null");
-
-            if !scan_tokens.errors.is_empty() {
-                std::process::exit(65);
+            for token in tokens {
+                println!("{token}");
             }
-        }
-        "parse" => {
-            let scan_tokens = scanner::scan_tokens(&file_contents);
 
-            if !scan_tokens.errors.is_empty() {
+            if !errors.is_empty() {
                 std::process::exit(65);
             }
-
-            let tree = parser::parse_tokens(&scan_tokens.tokens);
-
-            println!("{tree}");
-        }
-        "evaluate" => {
-            let scan_tokens = scanner::scan_tokens(&file_contents);
-            let tree = parser::parse_tokens(&scan_tokens.tokens);
-            let result = evaluate::evaluate(&tree, &Scope::new(HashMap::new(), None));
-
-            println!("{result}");
         }
+        "parse" => match interpreter_starter_rust::parse(&file_contents()) {
+            Ok(tree) => println!("{tree}"),
+            Err(error) => exit_with(&error),
+        },
+        "evaluate" => match Interpreter::default().eval_str(&file_contents()) {
+            Ok(value) => println!("{value}"),
+            Err(error) => exit_with(&error),
+        },
         "run" => {
-            let scan_tokens = scanner::scan_tokens(&file_contents);
-            let tree = parser::parse_tokens(&scan_tokens.tokens);
-            let scope = Scope::global();
-            evaluate::evaluate(&tree, &scope);
+            if let Err(error) = Interpreter::new(args.options).run_file(filename) {
+                exit_with(&error);
+            }
         }
         _ => {
             eprintln!("Unknown command: {command}");
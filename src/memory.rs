@@ -0,0 +1,77 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+// Global allocator that tracks live heap bytes per thread. An interpreter never
+// leaves the thread it was created on, so this is what `max_memory` measures;
+// embedders that set the limit must install it with `#[global_allocator]`.
+pub struct CountingAllocator;
+
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static LIVE: Cell<isize> = const { Cell::new(0) };
+}
+
+#[allow(clippy::cast_possible_wrap)]
+fn record(delta: isize) {
+    // Fails only while the thread is being torn down
+    let _ = LIVE.try_with(|live| live.set(live.get() + delta));
+}
+
+fn installed() {
+    if !INSTALLED.load(Ordering::Relaxed) {
+        INSTALLED.store(true, Ordering::Relaxed);
+    }
+}
+
+#[allow(clippy::cast_possible_wrap)]
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        installed();
+
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record(layout.size() as isize);
+        }
+
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        installed();
+
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record(layout.size() as isize);
+        }
+
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        record(-(layout.size() as isize));
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            record(new_size as isize - layout.size() as isize);
+        }
+
+        new_ptr
+    }
+}
+
+// Bytes currently allocated by this thread, or `None` when the counting
+// allocator isn't in use
+pub fn live_bytes() -> Option<isize> {
+    if !INSTALLED.load(Ordering::Relaxed) {
+        return None;
+    }
+
+    Some(LIVE.try_with(Cell::get).unwrap_or(0))
+}
@@ -0,0 +1,22 @@
+use core::fmt;
+
+#[derive(Clone, Debug)]
+pub enum Error {
+    Syntax(String),
+    // Calling a number or string literal, reported like a runtime error
+    Runtime(String),
+}
+
+impl Error {
+    pub fn syntax(message: impl Into<String>) -> Self {
+        Error::Syntax(message.into())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Syntax(message) | Error::Runtime(message) => write!(fmt, "{message}"),
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use core::fmt;
+use std::rc::Rc;
 
+use super::Error;
 use crate::{scanner::Type, utils::pad_number};
 
 #[derive(Clone, Debug)]
@@ -17,12 +19,16 @@ impl fmt::Display for UnaryOperator {
     }
 }
 
-impl From<Type> for UnaryOperator {
-    fn from(value: Type) -> Self {
+impl TryFrom<Type> for UnaryOperator {
+    type Error = Error;
+
+    fn try_from(value: Type) -> Result<Self, Error> {
         match value {
-            Type::Bang => UnaryOperator::Bang,
-            Type::Minus => UnaryOperator::Minus,
-            _ => panic!("Unknown token type"),
+            Type::Bang => Ok(UnaryOperator::Bang),
+            Type::Minus => Ok(UnaryOperator::Minus),
+            _ => Err(Error::syntax(format!(
+                "Error: Unknown unary operator {value}."
+            ))),
         }
     }
 }
@@ -39,6 +45,9 @@ pub enum BinaryOperator {
     Plus,
     Minus,
     Slash,
+    Percent,
+    Backslash,
+    StarStar,
 }
 
 impl fmt::Display for BinaryOperator {
@@ -54,13 +63,18 @@ impl fmt::Display for BinaryOperator {
             Self::Plus => write!(fmt, "+"),
             Self::Minus => write!(fmt, "-"),
             Self::Slash => write!(fmt, "/"),
+            Self::Percent => write!(fmt, "%"),
+            Self::Backslash => write!(fmt, "\\"),
+            Self::StarStar => write!(fmt, "**"),
         }
     }
 }
 
-impl From<Type> for BinaryOperator {
-    fn from(value: Type) -> Self {
-        match value {
+impl TryFrom<Type> for BinaryOperator {
+    type Error = Error;
+
+    fn try_from(value: Type) -> Result<Self, Error> {
+        let operator = match value {
             Type::BangEqual => BinaryOperator::BangEqual,
             Type::EqualEqual => BinaryOperator::EqualEqual,
             Type::Less => BinaryOperator::Less,
@@ -71,39 +85,75 @@ impl From<Type> for BinaryOperator {
             Type::Plus => BinaryOperator::Plus,
             Type::Minus => BinaryOperator::Minus,
             Type::Slash => BinaryOperator::Slash,
-            _ => panic!("Unknown token type"),
-        }
+            Type::Percent => BinaryOperator::Percent,
+            Type::Backslash => BinaryOperator::Backslash,
+            Type::StarStar => BinaryOperator::StarStar,
+            Type::PlusEqual | Type::PlusPlus => BinaryOperator::Plus,
+            Type::MinusEqual | Type::MinusMinus => BinaryOperator::Minus,
+            Type::StarEqual => BinaryOperator::Star,
+            Type::SlashEqual => BinaryOperator::Slash,
+            _ => {
+                return Err(Error::syntax(format!(
+                    "Error: Unknown binary operator {value}."
+                )))
+            }
+        };
+
+        Ok(operator)
     }
 }
 
+// Where the resolver placed a local variable: `depth` scopes out from the one
+// it is used in, at `index` among that scope's slots
+#[derive(Clone, Copy, Debug)]
+pub struct Slot {
+    pub depth: usize,
+    pub index: usize,
+}
+
+// Names of the variables declared directly in a scope, in slot order
+pub type Locals = Rc<[String]>;
+
 #[derive(Clone, Debug)]
 pub enum Expr {
     True,
     False,
     Nil,
-    String(String),
+    String(Rc<str>),
+    Interpolation(Vec<Expr>, usize),
     Number(f64),
-    Unary(UnaryOperator, Box<Expr>),
-    Binary(BinaryOperator, Box<Expr>, Box<Expr>),
+    Unary(UnaryOperator, Box<Expr>, usize),
+    Binary(BinaryOperator, Box<Expr>, Box<Expr>, usize),
     Grouping(Box<Expr>),
-    Identifier(String),
-    Assignment(String, Box<Expr>, bool),
-    Print(Box<Expr>),
+    // Variables without a slot are globals, looked up by name
+    Identifier(String, Option<Slot>, usize),
+    Assignment(String, Option<Slot>, Box<Expr>, bool, usize),
+    Postfix(String, Option<Slot>, Box<Expr>, usize),
+    Print(Box<Expr>, usize),
     Semicolon,
-    Statements(Vec<Expr>),
+    // Blocks without locals of their own run in the enclosing scope
+    Statements(Vec<Expr>, Locals),
     IfElse(Box<Expr>, Box<Expr>, Option<Box<Expr>>),
+    Ternary(Box<Expr>, Box<Expr>, Box<Expr>),
     Or(Box<Expr>, Box<Expr>),
     And(Box<Expr>, Box<Expr>),
-    While(Box<Expr>, Box<Expr>),
+    While(Box<Expr>, Box<Expr>, usize),
     For(
         Option<Box<Expr>>,
         Option<Box<Expr>>,
         Option<Box<Expr>>,
         Box<Expr>,
+        usize,
     ),
-    Callable(String, Vec<Vec<Expr>>),
-    Fun(String, Vec<String>, Box<Expr>),
+    Callable(String, Option<Slot>, Vec<Vec<Expr>>, usize),
+    // The locals start with the parameters, followed by the body's declarations
+    Fun(String, Option<Slot>, Vec<String>, Rc<Expr>, Locals),
     Return(Box<Expr>),
+    // `return f(...)` with nothing left to do in the function afterwards
+    TailCall(String, Option<Slot>, Vec<Vec<Expr>>, usize),
+    Throw(Box<Expr>, usize),
+    Import(String, Option<Vec<String>>, usize),
+    Try(Box<Expr>, Option<(String, Box<Expr>)>, Option<Box<Expr>>),
 }
 
 impl fmt::Display for Expr {
@@ -114,30 +164,45 @@ impl fmt::Display for Expr {
             Self::Nil => write!(fmt, "nil"),
             Self::Number(number) => write!(fmt, "{}", pad_number(*number)),
             Self::String(string) => write!(fmt, "{string}"),
+            Self::Interpolation(exprs, _) => {
+                write!(fmt, "(interpolation")?;
+
+                for expr in exprs {
+                    write!(fmt, " {expr}")?;
+                }
+
+                write!(fmt, ")")
+            }
             Self::Grouping(expr) => write!(fmt, "(group {expr})"),
-            Self::Unary(operator, expr) => write!(fmt, "({operator} {expr})"),
-            Self::Binary(operator, expr1, expr2) => write!(fmt, "({operator} {expr1} {expr2})"),
-            Self::Identifier(string) => write!(fmt, "identifier {string}"),
-            Self::Assignment(name, expr, _) => write!(fmt, "assignment {name} = {expr}"),
-            Self::Print(expr) => write!(fmt, "print {expr}"),
+            Self::Unary(operator, expr, _) => write!(fmt, "({operator} {expr})"),
+            Self::Binary(operator, expr1, expr2, _) => {
+                write!(fmt, "({operator} {expr1} {expr2})")
+            }
+            Self::Identifier(string, _, _) => write!(fmt, "identifier {string}"),
+            Self::Assignment(name, _, expr, _, _) => write!(fmt, "assignment {name} = {expr}"),
+            Self::Postfix(name, _, expr, _) => write!(fmt, "postfix {name} {expr}"),
+            Self::Print(expr, _) => write!(fmt, "print {expr}"),
             Self::Semicolon => write!(fmt, "semicolon"),
-            Self::Statements(exprs) => {
+            Self::Statements(exprs, _) => {
                 let len = exprs.len();
                 write!(fmt, "statements {len}")
             }
             Self::IfElse(expr1, expr2, _) => {
                 write!(fmt, "if {expr1} then {expr2}")
             }
+            Self::Ternary(condition, then, otherwise) => {
+                write!(fmt, "(?: {condition} {then} {otherwise})")
+            }
             Self::Or(left, right) => {
                 write!(fmt, "{left} or {right}")
             }
             Self::And(left, right) => {
                 write!(fmt, "{left} and {right}")
             }
-            Self::While(expr1, expr2) => {
+            Self::While(expr1, expr2, _) => {
                 write!(fmt, "while ({expr1}) {expr2}")
             }
-            Self::For(expr1, expr2, expr3, expr4) => {
+            Self::For(expr1, expr2, expr3, expr4, _) => {
                 write!(
                     fmt,
                     "for ({};{};{}) {}",
@@ -147,15 +212,24 @@ impl fmt::Display for Expr {
                     expr4
                 )
             }
-            Self::Callable(name, _args) => {
+            Self::Callable(name, _, _args, _) => {
                 write!(fmt, "callbale {name}")
             }
-            Self::Fun(name, _args, _expr) => {
+            Self::Fun(name, _, _args, _expr, _) => {
                 write!(fmt, "fun {name}")
             }
-            Self::Return(_expr) => {
+            Self::Return(_) | Self::TailCall(..) => {
                 write!(fmt, "return")
             }
+            Self::Throw(expr, _) => {
+                write!(fmt, "throw {expr}")
+            }
+            Self::Import(path, _names, _) => {
+                write!(fmt, "import {path}")
+            }
+            Self::Try(_body, _catch, _finally) => {
+                write!(fmt, "try")
+            }
         }
     }
 }
@@ -1,7 +1,13 @@
+mod error;
 mod expr;
+#[allow(clippy::module_inception)]
 mod parser;
 
+pub use error::Error;
 pub(crate) use expr::Expr;
 pub(crate) use expr::UnaryOperator;
 pub(crate) use expr::BinaryOperator;
+pub(crate) use expr::Locals;
+pub(crate) use expr::Slot;
+pub(crate) use parser::parse_statements;
 pub(crate) use parser::parse_tokens;
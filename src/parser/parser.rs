@@ -1,28 +1,114 @@
-use std::iter::Peekable;
+use std::{iter::Peekable, rc::Rc};
 
 use crate::scanner::{Token, Type};
 
-use super::expr::{BinaryOperator, Expr, UnaryOperator};
+use super::{
+    error::Error,
+    expr::{BinaryOperator, Expr, Locals, UnaryOperator},
+};
 
-fn unary<'a, I>(tokens: &mut Peekable<I>) -> Expr
+fn unary<'a, I>(tokens: &mut Peekable<I>) -> Result<Expr, Error>
 where
     I: Iterator<Item = &'a Token>,
 {
-    if let Some(r#type) = next_type_match(&[Type::Bang, Type::Minus], tokens) {
-        let right = unary(tokens);
-        let operator: UnaryOperator = r#type.into();
+    if let Some(token) = next_token_match(&[Type::Bang, Type::Minus], tokens) {
+        let right = unary(tokens)?;
+        let operator = UnaryOperator::try_from(token.ty)?;
 
-        return Expr::Unary(operator, Box::new(right));
+        return Ok(Expr::Unary(operator, Box::new(right), token.line));
     }
 
-    primary(tokens)
+    if let Some(token) = next_token_match(&[Type::PlusPlus, Type::MinusMinus], tokens) {
+        if let Some(identifier) = tokens.next_if(|token| token.ty == Type::Identifier) {
+            return increment(&identifier.lexeme, token.ty, token.line);
+        }
+
+        return Err(Error::syntax("Error: Invalid increment target."));
+    }
+
+    power(tokens)
+}
+
+// `x += y` and `++x` are desugared into `x = x + y` and `x = x + 1`
+fn compound_assignment(name: &str, r#type: Type, expr: Expr, line: usize) -> Result<Expr, Error> {
+    let operator = BinaryOperator::try_from(r#type)?;
+    let value = Expr::Binary(
+        operator,
+        Box::new(Expr::Identifier(name.to_string(), None, line)),
+        Box::new(expr),
+        line,
+    );
+
+    Ok(Expr::Assignment(
+        name.to_string(),
+        None,
+        Box::new(value),
+        false,
+        line,
+    ))
+}
+
+fn increment(name: &str, r#type: Type, line: usize) -> Result<Expr, Error> {
+    compound_assignment(name, r#type, Expr::Number(1.0), line)
+}
+
+fn tail_calls(expr: Expr) -> Expr {
+    match expr {
+        Expr::Return(value) => match *value {
+            Expr::Callable(name, slot, args, line) => Expr::TailCall(name, slot, args, line),
+            value => Expr::Return(Box::new(value)),
+        },
+        Expr::Statements(exprs, locals) => {
+            Expr::Statements(exprs.into_iter().map(tail_calls).collect(), locals)
+        }
+        Expr::IfElse(condition, then, otherwise) => Expr::IfElse(
+            condition,
+            Box::new(tail_calls(*then)),
+            otherwise.map(|otherwise| Box::new(tail_calls(*otherwise))),
+        ),
+        Expr::While(condition, body, line) => {
+            Expr::While(condition, Box::new(tail_calls(*body)), line)
+        }
+        Expr::For(init, condition, increment, body, line) => Expr::For(
+            init,
+            condition,
+            increment,
+            Box::new(tail_calls(*body)),
+            line,
+        ),
+        // A return inside `try` still has handlers to run after the call, and
+        // nested functions were already handled when they were parsed
+        expr => expr,
+    }
+}
+
+fn power<'a, I>(tokens: &mut Peekable<I>) -> Result<Expr, Error>
+where
+    I: Iterator<Item = &'a Token>,
+{
+    let expr = primary(tokens)?;
+
+    if let Some(token) = next_token_match(&[Type::StarStar], tokens) {
+        // Right operand goes back through unary so `2 ** -1` and `2 ** 3 ** 2` work
+        let right = unary(tokens)?;
+        let operator = BinaryOperator::try_from(token.ty)?;
+
+        return Ok(Expr::Binary(
+            operator,
+            Box::new(expr),
+            Box::new(right),
+            token.line,
+        ));
+    }
+
+    Ok(expr)
 }
 
 fn next_type_match<'a, I>(types: &[Type], tokens: &mut Peekable<I>) -> Option<Type>
 where
     I: Iterator<Item = &'a Token>,
 {
-    let token = tokens.next_if(|token| types.contains(&token.ty));
+    let token = next_token_match(types, tokens);
 
     if let Some(token) = token {
         return Some(token.ty);
@@ -31,402 +117,587 @@ where
     None
 }
 
-#[allow(clippy::too_many_lines)]
-fn primary<'a, I>(tokens: &mut Peekable<I>) -> Expr
+fn next_token_match<'a, I>(types: &[Type], tokens: &mut Peekable<I>) -> Option<&'a Token>
 where
     I: Iterator<Item = &'a Token>,
 {
-    if let Some(token) = tokens.next() {
-        let line = token.line;
-
-        match token.ty {
-            Type::True => Expr::True,
-            Type::False => Expr::False,
-            Type::Nil => Expr::Nil,
-            Type::Number => {
-                if next_type_match(&[Type::LeftParen], tokens).is_some() {
-                    eprintln!("Can only call functions and classes.");
-                    std::process::exit(70);
-                }
+    tokens.next_if(|token| types.contains(&token.ty))
+}
 
-                let value = token.lexeme.parse::<f64>().unwrap();
-                Expr::Number(value)
+#[allow(clippy::too_many_lines)]
+fn primary<'a, I>(tokens: &mut Peekable<I>) -> Result<Expr, Error>
+where
+    I: Iterator<Item = &'a Token>,
+{
+    let Some(token) = tokens.next() else {
+        return Err(Error::syntax("Error: Expected token."));
+    };
+
+    let line = token.line;
+
+    let expr = match token.ty {
+        Type::True => Expr::True,
+        Type::False => Expr::False,
+        Type::Nil => Expr::Nil,
+        Type::Number => {
+            if next_type_match(&[Type::LeftParen], tokens).is_some() {
+                return Err(Error::Runtime(String::from(
+                    "Can only call functions and classes.",
+                )));
             }
-            Type::String => {
-                if next_type_match(&[Type::LeftParen], tokens).is_some() {
-                    eprintln!("Can only call functions and classes.");
-                    std::process::exit(70);
-                }
 
-                let literal = &token.literal;
-                let string = literal.clone().unwrap();
-                Expr::String(string.to_string())
+            let value = token.lexeme.parse::<f64>().unwrap();
+            Expr::Number(value)
+        }
+        Type::String => {
+            if next_type_match(&[Type::LeftParen], tokens).is_some() {
+                return Err(Error::Runtime(String::from(
+                    "Can only call functions and classes.",
+                )));
             }
-            Type::LeftParen => {
-                let expr = expression(tokens);
 
-                if next_type_match(&[Type::RightParen], tokens).is_none() {
-                    eprintln!("Error: Unmatched parentheses.");
-                    std::process::exit(65);
-                }
+            let literal = &token.literal;
+            let string = literal.clone().unwrap();
+            Expr::String(string.into())
+        }
+        Type::Interpolation => {
+            let mut exprs = vec![];
+            let mut token = token;
 
-                if next_type_match(&[Type::LeftParen], tokens).is_some() {
-                    eprintln!("Error: Can only call functions and classes.");
-                    std::process::exit(70);
-                }
+            loop {
+                let string = token.literal.clone().unwrap();
 
-                Expr::Grouping(Box::new(expr))
-            }
-            Type::LeftBrace => {
-                if next_type_match(&[Type::RightBrace], tokens).is_some() {
-                    return Expr::Nil;
+                if !string.is_empty() {
+                    exprs.push(Expr::String(string.into()));
                 }
 
-                let mut statements = vec![];
+                if token.ty == Type::String {
+                    break;
+                }
 
-                while tokens.peek().is_some() {
-                    statements.push(expression(tokens));
+                exprs.push(expression(tokens)?);
 
-                    if let Some(token) = tokens.peek() {
-                        if token.ty == Type::RightBrace {
-                            break;
-                        }
+                match tokens.next_if(|token| matches!(token.ty, Type::String | Type::Interpolation))
+                {
+                    Some(next) => token = next,
+                    None => {
+                        return Err(Error::syntax(format!(
+                            "[line {line}] Error: Unterminated string interpolation."
+                        )));
                     }
                 }
+            }
 
-                if next_type_match(&[Type::RightBrace], tokens).is_none() {
-                    eprintln!("Error: Unmatched braces.");
-                    std::process::exit(65);
-                }
+            Expr::Interpolation(exprs, line)
+        }
+        Type::LeftParen => {
+            let expr = expression(tokens)?;
 
-                Expr::Statements(statements)
+            if next_type_match(&[Type::RightParen], tokens).is_none() {
+                return Err(Error::syntax("Error: Unmatched parentheses."));
             }
-            Type::Identifier => {
-                let lexeme = &token.lexeme;
 
-                let token = tokens.peek();
-                if let Some(value) = token {
-                    if value.ty == Type::Equal {
-                        tokens.next();
-                        let expr = expression(tokens);
-                        return Expr::Assignment(lexeme.clone(), Box::new(expr), false);
+            if next_type_match(&[Type::LeftParen], tokens).is_some() {
+                return Err(Error::Runtime(String::from(
+                    "Error: Can only call functions and classes.",
+                )));
+            }
+
+            Expr::Grouping(Box::new(expr))
+        }
+        Type::LeftBrace => {
+            if next_type_match(&[Type::RightBrace], tokens).is_some() {
+                return Ok(Expr::Nil);
+            }
+
+            let mut statements = vec![];
+
+            while tokens.peek().is_some() {
+                statements.push(expression(tokens)?);
+
+                if let Some(token) = tokens.peek() {
+                    if token.ty == Type::RightBrace {
+                        break;
                     }
+                }
+            }
 
-                    let mut ty = value.ty;
-                    let mut args = vec![];
+            if next_type_match(&[Type::RightBrace], tokens).is_none() {
+                return Err(Error::syntax("Error: Unmatched braces."));
+            }
 
-                    while ty == Type::LeftParen {
-                        tokens.next();
+            Expr::Statements(statements, Locals::default())
+        }
+        Type::Identifier => {
+            let lexeme = &token.lexeme;
+
+            let token = tokens.peek();
+            if let Some(value) = token {
+                if value.ty == Type::Equal {
+                    tokens.next();
+                    let expr = expression(tokens)?;
+                    return Ok(Expr::Assignment(
+                        lexeme.clone(),
+                        None,
+                        Box::new(expr),
+                        false,
+                        line,
+                    ));
+                }
 
-                        let mut args_exprs = vec![];
+                if matches!(
+                    value.ty,
+                    Type::PlusEqual | Type::MinusEqual | Type::StarEqual | Type::SlashEqual
+                ) {
+                    let r#type = value.ty;
+                    tokens.next();
+                    let expr = expression(tokens)?;
+                    return compound_assignment(lexeme, r#type, expr, line);
+                }
 
-                        while let Some(token) = tokens.peek() {
-                            if token.ty == Type::RightParen {
-                                tokens.next();
-                                break;
-                            }
+                if matches!(value.ty, Type::PlusPlus | Type::MinusMinus) {
+                    let expr = increment(lexeme, value.ty, line)?;
+                    tokens.next();
+                    return Ok(Expr::Postfix(lexeme.clone(), None, Box::new(expr), line));
+                }
 
-                            if token.ty == Type::Comma {
-                                tokens.next();
-                                continue;
-                            }
+                let mut ty = value.ty;
+                let mut args = vec![];
 
-                            if token.ty == Type::Semicolon {
-                                eprintln!("[line {line}] Error Unexpected Semicolon");
-                                std::process::exit(65);
-                            }
+                while ty == Type::LeftParen {
+                    tokens.next();
 
-                            let expr = expression(tokens);
+                    let mut args_exprs = vec![];
 
-                            args_exprs.push(expr);
+                    while let Some(token) = tokens.peek() {
+                        if token.ty == Type::RightParen {
+                            tokens.next();
+                            break;
                         }
 
-                        args.push(args_exprs);
+                        if token.ty == Type::Comma {
+                            tokens.next();
+                            continue;
+                        }
 
-                        let token = tokens.peek();
-                        if let Some(token) = token {
-                            ty = token.ty;
+                        if token.ty == Type::Semicolon {
+                            return Err(Error::syntax(format!(
+                                "[line {line}] Error Unexpected Semicolon"
+                            )));
                         }
-                    }
 
-                    if !args.is_empty() {
-                        return Expr::Callable(lexeme.clone(), args);
+                        let expr = expression(tokens)?;
+
+                        args_exprs.push(expr);
                     }
 
-                    return Expr::Identifier(lexeme.clone());
-                }
+                    args.push(args_exprs);
 
-                Expr::Nil
-            }
-            Type::Var => {
-                let token = tokens.next();
-                let mut var = String::new();
-                let mut expr = Expr::Nil;
-
-                if let Some(value) = token {
-                    if value.ty == Type::Identifier {
-                        var = String::from(&value.lexeme);
-                    } else {
-                        eprintln!("Error: Expected identifier.");
-                        std::process::exit(65);
-                    }
+                    // A call can end the input when there is no trailing semicolon
+                    let Some(token) = tokens.peek() else {
+                        break;
+                    };
+
+                    ty = token.ty;
                 }
 
-                let token = tokens.next();
-                if let Some(value) = token {
-                    if value.ty == Type::Equal {
-                        expr = expression(tokens);
-                    } else if value.ty == Type::Semicolon {
-                        expr = Expr::Semicolon;
-                    } else {
-                        eprintln!("Error: Expected '=' or ';'.");
-                        std::process::exit(65);
-                    }
+                if !args.is_empty() {
+                    return Ok(Expr::Callable(lexeme.clone(), None, args, line));
                 }
 
-                Expr::Assignment(var, Box::new(expr), true)
+                return Ok(Expr::Identifier(lexeme.clone(), None, line));
             }
-            Type::Print => {
-                let expr = expression(tokens);
 
-                if let Expr::Semicolon = expr {
-                    eprintln!("Error: Print statement must have an expression.");
-                    std::process::exit(65);
+            Expr::Identifier(lexeme.clone(), None, line)
+        }
+        Type::Var => {
+            let token = tokens.next();
+            let mut var = String::new();
+            let mut expr = Expr::Nil;
+
+            if let Some(value) = token {
+                if value.ty == Type::Identifier {
+                    var = String::from(&value.lexeme);
+                } else {
+                    return Err(Error::syntax("Error: Expected identifier."));
                 }
+            }
 
-                Expr::Print(Box::new(expr))
+            let token = tokens.next();
+            if let Some(value) = token {
+                if value.ty == Type::Equal {
+                    expr = expression(tokens)?;
+                } else if value.ty == Type::Semicolon {
+                    expr = Expr::Semicolon;
+                } else {
+                    return Err(Error::syntax("Error: Expected '=' or ';'."));
+                }
             }
-            // TODO: remove Expr::Semicolon and use next_type_match function instead
-            Type::Semicolon => Expr::Semicolon,
-            Type::If => {
-                let expr1 = expression(tokens);
-                let expr2 = expression(tokens);
 
-                if let Some(token) = tokens.peek() {
-                    let mut token_type = token.ty;
+            Expr::Assignment(var, None, Box::new(expr), true, line)
+        }
+        Type::Print => {
+            let expr = expression(tokens)?;
+
+            if let Expr::Semicolon = expr {
+                return Err(Error::syntax(
+                    "Error: Print statement must have an expression.",
+                ));
+            }
 
-                    if token_type == Type::Semicolon {
-                        tokens.next();
+            Expr::Print(Box::new(expr), line)
+        }
+        // TODO: remove Expr::Semicolon and use next_type_match function instead
+        Type::Semicolon => Expr::Semicolon,
+        Type::If => {
+            let expr1 = expression(tokens)?;
+            let expr2 = expression(tokens)?;
 
-                        if let Some(token) = tokens.peek() {
-                            token_type = token.ty;
-                        }
-                    }
+            if let Some(token) = tokens.peek() {
+                let mut token_type = token.ty;
 
-                    if token_type == Type::Else {
-                        tokens.next();
+                if token_type == Type::Semicolon {
+                    tokens.next();
 
-                        let else_expr = expression(tokens);
-                        return Expr::IfElse(
-                            Box::new(expr1),
-                            Box::new(expr2),
-                            Some(Box::new(else_expr)),
-                        );
+                    if let Some(token) = tokens.peek() {
+                        token_type = token.ty;
                     }
                 }
 
-                Expr::IfElse(Box::new(expr1), Box::new(expr2), None)
-            }
-            Type::While => {
-                let expr1 = expression(tokens);
-                let expr2 = expression(tokens);
+                if token_type == Type::Else {
+                    tokens.next();
 
-                Expr::While(Box::new(expr1), Box::new(expr2))
+                    let else_expr = expression(tokens)?;
+                    return Ok(Expr::IfElse(
+                        Box::new(expr1),
+                        Box::new(expr2),
+                        Some(Box::new(else_expr)),
+                    ));
+                }
             }
-            Type::Fun => {
-                let token = tokens.next();
-                let mut name = String::new();
-                let mut args = vec![];
 
-                if let Some(value) = token {
-                    if value.ty == Type::Identifier {
-                        name = String::from(&value.lexeme);
-                    } else {
-                        eprintln!("[line {line}] Error Expected Identifier");
-                        std::process::exit(65);
-                    }
+            Expr::IfElse(Box::new(expr1), Box::new(expr2), None)
+        }
+        Type::While => {
+            let expr1 = expression(tokens)?;
+            let expr2 = expression(tokens)?;
+
+            Expr::While(Box::new(expr1), Box::new(expr2), line)
+        }
+        Type::Fun => {
+            let token = tokens.next();
+            let mut name = String::new();
+            let mut args = vec![];
+
+            if let Some(value) = token {
+                if value.ty == Type::Identifier {
+                    name = String::from(&value.lexeme);
+                } else {
+                    return Err(Error::syntax(format!(
+                        "[line {line}] Error Expected Identifier"
+                    )));
                 }
+            }
 
-                let token = tokens.next();
+            let token = tokens.next();
 
-                if let Some(value) = token {
-                    if value.ty == Type::LeftParen {
-                        while let Some(token) = tokens.peek() {
-                            if token.ty == Type::RightParen {
-                                tokens.next();
-                                break;
-                            }
+            if let Some(value) = token {
+                if value.ty == Type::LeftParen {
+                    while let Some(token) = tokens.peek() {
+                        if token.ty == Type::RightParen {
+                            tokens.next();
+                            break;
+                        }
 
-                            if token.ty == Type::Comma {
-                                tokens.next();
-                                continue;
-                            }
+                        if token.ty == Type::Comma {
+                            tokens.next();
+                            continue;
+                        }
 
-                            if Type::Identifier == token.ty {
-                                args.push(token.lexeme.clone());
-                                tokens.next();
+                        if Type::Identifier == token.ty {
+                            args.push(token.lexeme.clone());
+                            tokens.next();
 
-                                let token = tokens.peek();
+                            let token = tokens.peek();
 
-                                if let Some(token) = token {
-                                    if token.ty != Type::RightParen && token.ty != Type::Comma {
-                                        eprintln!("[line {line}] Missing comma");
-                                        std::process::exit(65);
-                                    }
+                            if let Some(token) = token {
+                                if token.ty != Type::RightParen && token.ty != Type::Comma {
+                                    return Err(Error::syntax(format!(
+                                        "[line {line}] Missing comma"
+                                    )));
                                 }
-                            } else {
-                                eprintln!("[line {line}] Error Expected Identifier");
-                                std::process::exit(65);
                             }
+                        } else {
+                            return Err(Error::syntax(format!(
+                                "[line {line}] Error Expected Identifier"
+                            )));
                         }
                     }
                 }
+            }
 
-                let token = tokens.peek();
+            let token = tokens.peek();
 
-                if let Some(value) = token {
-                    if value.ty != Type::LeftBrace {
-                        eprintln!("[line {line}] Error LeftBrace");
-                        std::process::exit(65);
-                    }
+            if let Some(value) = token {
+                if value.ty != Type::LeftBrace {
+                    return Err(Error::syntax(format!("[line {line}] Error LeftBrace")));
                 }
+            }
 
-                let expr = expression(tokens);
+            let expr = expression(tokens)?;
 
-                Expr::Fun(name, args, Box::new(expr))
-            }
-            Type::For => {
-                if next_type_match(&[Type::LeftParen], tokens).is_some() {
-                    let mut expr1 = None;
+            Expr::Fun(
+                name,
+                None,
+                args,
+                Rc::new(tail_calls(expr)),
+                Locals::default(),
+            )
+        }
+        Type::For => {
+            if next_type_match(&[Type::LeftParen], tokens).is_some() {
+                let mut expr1 = None;
+
+                // TODO: REFACTOR THIS PLEASE
+                if next_type_match(&[Type::Semicolon], tokens).is_none() {
+                    let token = tokens
+                        .peek()
+                        .ok_or_else(|| Error::syntax("Error: Expected token."))?;
+                    let lexeme = &token.lexeme;
+                    let expr = expression(tokens)?;
+
+                    if let Expr::Nil = expr {
+                        return Err(Error::syntax(format!("[line {line}] Error at {lexeme}")));
+                    }
+
+                    expr1 = Some(Box::new(expr));
 
-                    // TODO: REFACTOR THIS PLEASE
                     if next_type_match(&[Type::Semicolon], tokens).is_none() {
-                        let token = tokens.peek().unwrap();
-                        let lexeme = &token.lexeme;
-                        let expr = expression(tokens);
+                        eprintln!("Token should be semicolon 1");
+                    }
+                }
 
-                        if let Expr::Nil = expr {
-                            eprintln!("[line {line}] Error at {lexeme}");
-                            std::process::exit(65);
-                        }
+                let mut expr2 = None;
 
-                        expr1 = Some(Box::new(expr));
+                if next_type_match(&[Type::Semicolon], tokens).is_none() {
+                    let token = tokens
+                        .peek()
+                        .ok_or_else(|| Error::syntax("Error: Expected token."))?;
+                    let lexeme = &token.lexeme;
+                    let expr = expression(tokens)?;
 
-                        if next_type_match(&[Type::Semicolon], tokens).is_none() {
-                            eprintln!("Token should be semicolon 1");
-                        }
+                    if let Expr::Nil = expr {
+                        return Err(Error::syntax(format!("[line {line}] Error at {lexeme}")));
                     }
 
-                    let mut expr2 = None;
+                    expr2 = Some(Box::new(expr));
 
                     if next_type_match(&[Type::Semicolon], tokens).is_none() {
-                        let token = tokens.peek().unwrap();
-                        let lexeme = &token.lexeme;
-                        let expr = expression(tokens);
+                        eprintln!("Token should be semicolon 2");
+                    }
+                }
 
-                        if let Expr::Nil = expr {
-                            eprintln!("[line {line}] Error at {lexeme}");
-                            std::process::exit(65);
-                        }
+                let mut expr3 = None;
 
-                        expr2 = Some(Box::new(expr));
+                if next_type_match(&[Type::RightParen], tokens).is_none() {
+                    let token = tokens
+                        .peek()
+                        .ok_or_else(|| Error::syntax("Error: Expected token."))?;
+                    let lexeme = &token.lexeme;
+                    let expr = expression(tokens)?;
 
-                        if next_type_match(&[Type::Semicolon], tokens).is_none() {
-                            eprintln!("Token should be semicolon 2");
-                        }
+                    if let Expr::Nil = expr {
+                        return Err(Error::syntax(format!("[line {line}] Error at {lexeme}")));
                     }
 
-                    let mut expr3 = None;
+                    expr3 = Some(Box::new(expr));
 
                     if next_type_match(&[Type::RightParen], tokens).is_none() {
-                        let token = tokens.peek().unwrap();
-                        let lexeme = &token.lexeme;
-                        let expr = expression(tokens);
+                        eprintln!("Token should be semicolon 3");
+                    }
+                }
 
-                        if let Expr::Nil = expr {
-                            eprintln!("[line {line}] Error at {lexeme}");
-                            std::process::exit(65);
-                        }
+                let token = tokens
+                    .peek()
+                    .ok_or_else(|| Error::syntax("Error: Expected token."))?;
+                let lexeme = &token.lexeme;
+                let expr4 = expression(tokens)?;
 
-                        expr3 = Some(Box::new(expr));
+                if !matches!(expr4, Expr::Statements(..) | Expr::Print(..) | Expr::Nil) {
+                    return Err(Error::syntax(format!("[line {line}] Error at {lexeme}")));
+                }
 
-                        if next_type_match(&[Type::RightParen], tokens).is_none() {
-                            eprintln!("Token should be semicolon 3");
-                        }
-                    }
+                return Ok(Expr::For(expr1, expr2, expr3, Box::new(expr4), line));
+            }
 
-                    let token = tokens.peek().unwrap();
-                    let lexeme = &token.lexeme;
-                    let expr4 = expression(tokens);
+            Expr::Nil
+        }
+        Type::Return => {
+            let expr = expression(tokens)?;
+            Expr::Return(Box::new(expr))
+        }
+        Type::Throw => {
+            let expr = expression(tokens)?;
+
+            if let Expr::Semicolon = expr {
+                return Err(Error::syntax(format!(
+                    "[line {line}] Error: Throw statement must have an expression."
+                )));
+            }
+
+            Expr::Throw(Box::new(expr), line)
+        }
+        Type::Import => {
+            let mut names = None;
+
+            if next_type_match(&[Type::LeftBrace], tokens).is_some() {
+                let mut list = vec![];
+
+                while let Some(name) = next_token_match(&[Type::Identifier], tokens) {
+                    list.push(name.lexeme.clone());
 
-                    if !matches!(expr4, Expr::Statements(_) | Expr::Print(_) | Expr::Nil) {
-                        eprintln!("[line {line}] Error at {lexeme}");
-                        std::process::exit(65);
+                    if next_type_match(&[Type::Comma], tokens).is_none() {
+                        break;
                     }
+                }
+
+                if next_type_match(&[Type::RightBrace], tokens).is_none() {
+                    return Err(Error::syntax(format!(
+                        "[line {line}] Error: Expect '}}' after import names."
+                    )));
+                }
+
+                // `from` is only special here, so it stays usable as an identifier
+                if tokens
+                    .next_if(|token| token.ty == Type::Identifier && token.lexeme == "from")
+                    .is_none()
+                {
+                    return Err(Error::syntax(format!(
+                        "[line {line}] Error: Expect 'from' after import names."
+                    )));
+                }
+
+                names = Some(list);
+            }
+
+            let Some(path) = next_token_match(&[Type::String], tokens) else {
+                return Err(Error::syntax(format!(
+                    "[line {line}] Error: Expect module path string."
+                )));
+            };
+
+            Expr::Import(path.literal.clone().unwrap(), names, line)
+        }
+        Type::Try => {
+            let body = block(tokens, line)?;
+            let mut catch = None;
+            let mut finally = None;
+
+            if next_type_match(&[Type::Catch], tokens).is_some() {
+                if next_type_match(&[Type::LeftParen], tokens).is_none() {
+                    return Err(Error::syntax(format!(
+                        "[line {line}] Error: Expect '(' after 'catch'."
+                    )));
+                }
+
+                let Some(name) = next_token_match(&[Type::Identifier], tokens) else {
+                    return Err(Error::syntax(format!(
+                        "[line {line}] Error: Expect catch variable name."
+                    )));
+                };
 
-                    return Expr::For(expr1, expr2, expr3, Box::new(expr4));
+                if next_type_match(&[Type::RightParen], tokens).is_none() {
+                    return Err(Error::syntax(format!(
+                        "[line {line}] Error: Expect ')' after catch variable."
+                    )));
                 }
 
-                Expr::Nil
+                let handler = block(tokens, line)?;
+                catch = Some((name.lexeme.clone(), Box::new(handler)));
             }
-            Type::Return => {
-                let expr = expression(tokens);
-                Expr::Return(Box::new(expr))
+
+            if next_type_match(&[Type::Finally], tokens).is_some() {
+                finally = Some(Box::new(block(tokens, line)?));
             }
-            _ => {
-                eprintln!("Error: Unknown token type {:?}.", token.ty);
-                std::process::exit(65);
+
+            if catch.is_none() && finally.is_none() {
+                return Err(Error::syntax(format!(
+                    "[line {line}] Error: Expect 'catch' or 'finally' after try block."
+                )));
             }
+
+            Expr::Try(Box::new(body), catch, finally)
         }
-    } else {
-        eprintln!("Error: Expected token.");
-        std::process::exit(65);
+        _ => {
+            return Err(Error::syntax(format!(
+                "Error: Unknown token type {:?}.",
+                token.ty
+            )));
+        }
+    };
+
+    Ok(expr)
+}
+
+fn block<'a, I>(tokens: &mut Peekable<I>, line: usize) -> Result<Expr, Error>
+where
+    I: Iterator<Item = &'a Token>,
+{
+    if !matches!(tokens.peek(), Some(token) if token.ty == Type::LeftBrace) {
+        return Err(Error::syntax(format!(
+            "[line {line}] Error: Expect '{{' before block."
+        )));
     }
+
+    expression(tokens)
 }
 
-fn factor<'a, I>(tokens: &mut Peekable<I>) -> Expr
+fn factor<'a, I>(tokens: &mut Peekable<I>) -> Result<Expr, Error>
 where
     I: Iterator<Item = &'a Token>,
 {
-    let mut expr = unary(tokens);
+    let mut expr = unary(tokens)?;
 
-    while let Some(r#type) = next_type_match(&[Type::Star, Type::Slash], tokens) {
+    while let Some(token) = next_token_match(
+        &[Type::Star, Type::Slash, Type::Percent, Type::Backslash],
+        tokens,
+    ) {
         let left = expr;
-        let right = unary(tokens);
+        let right = unary(tokens)?;
 
-        let operator: BinaryOperator = r#type.into();
+        let operator = BinaryOperator::try_from(token.ty)?;
 
-        expr = Expr::Binary(operator, Box::new(left), Box::new(right));
+        expr = Expr::Binary(operator, Box::new(left), Box::new(right), token.line);
     }
 
-    expr
+    Ok(expr)
 }
 
-fn term<'a, I>(tokens: &mut Peekable<I>) -> Expr
+fn term<'a, I>(tokens: &mut Peekable<I>) -> Result<Expr, Error>
 where
     I: Iterator<Item = &'a Token>,
 {
-    let mut expr = factor(tokens);
+    let mut expr = factor(tokens)?;
 
-    while let Some(r#type) = next_type_match(&[Type::Minus, Type::Plus], tokens) {
+    while let Some(token) = next_token_match(&[Type::Minus, Type::Plus], tokens) {
         let left = expr;
-        let right = factor(tokens);
+        let right = factor(tokens)?;
 
-        let operator: BinaryOperator = r#type.into();
+        let operator = BinaryOperator::try_from(token.ty)?;
 
-        expr = Expr::Binary(operator, Box::new(left), Box::new(right));
+        expr = Expr::Binary(operator, Box::new(left), Box::new(right), token.line);
     }
 
-    expr
+    Ok(expr)
 }
 
-fn comparison<'a, I>(tokens: &mut Peekable<I>) -> Expr
+fn comparison<'a, I>(tokens: &mut Peekable<I>) -> Result<Expr, Error>
 where
     I: Iterator<Item = &'a Token>,
 {
-    let mut expr = term(tokens);
+    let mut expr = term(tokens)?;
 
-    while let Some(r#type) = next_type_match(
+    while let Some(token) = next_token_match(
         &[
             Type::Greater,
             Type::GreaterEqual,
@@ -436,84 +707,117 @@ where
         tokens,
     ) {
         let left = expr;
-        let right = term(tokens);
+        let right = term(tokens)?;
 
-        let operator: BinaryOperator = r#type.into();
+        let operator = BinaryOperator::try_from(token.ty)?;
 
-        expr = Expr::Binary(operator, Box::new(left), Box::new(right));
+        expr = Expr::Binary(operator, Box::new(left), Box::new(right), token.line);
     }
 
-    expr
+    Ok(expr)
 }
 
-fn equality<'a, I>(tokens: &mut Peekable<I>) -> Expr
+fn equality<'a, I>(tokens: &mut Peekable<I>) -> Result<Expr, Error>
 where
     I: Iterator<Item = &'a Token>,
 {
-    let mut expr = comparison(tokens);
+    let mut expr = comparison(tokens)?;
 
-    while let Some(r#type) = next_type_match(&[Type::EqualEqual, Type::BangEqual], tokens) {
+    while let Some(token) = next_token_match(&[Type::EqualEqual, Type::BangEqual], tokens) {
         let left = expr;
-        let right = comparison(tokens);
+        let right = comparison(tokens)?;
 
-        let operator: BinaryOperator = r#type.into();
+        let operator = BinaryOperator::try_from(token.ty)?;
 
-        expr = Expr::Binary(operator, Box::new(left), Box::new(right));
+        expr = Expr::Binary(operator, Box::new(left), Box::new(right), token.line);
     }
 
-    expr
+    Ok(expr)
 }
 
-fn or<'a, I>(tokens: &mut Peekable<I>) -> Expr
+fn or<'a, I>(tokens: &mut Peekable<I>) -> Result<Expr, Error>
 where
     I: Iterator<Item = &'a Token>,
 {
-    let mut expr = and(tokens);
+    let mut expr = and(tokens)?;
 
     while next_type_match(&[Type::Or], tokens).is_some() {
         let left = expr;
-        let right = and(tokens);
+        let right = and(tokens)?;
 
         expr = Expr::Or(Box::new(left), Box::new(right));
     }
 
-    expr
+    Ok(expr)
 }
 
-fn and<'a, I>(tokens: &mut Peekable<I>) -> Expr
+fn and<'a, I>(tokens: &mut Peekable<I>) -> Result<Expr, Error>
 where
     I: Iterator<Item = &'a Token>,
 {
-    let mut expr = equality(tokens);
+    let mut expr = equality(tokens)?;
 
     while next_type_match(&[Type::And], tokens).is_some() {
         let left = expr;
-        let right = equality(tokens);
+        let right = equality(tokens)?;
 
         expr = Expr::And(Box::new(left), Box::new(right));
     }
 
-    expr
+    Ok(expr)
 }
 
-fn expression<'a, I>(tokens: &mut Peekable<I>) -> Expr
+fn ternary<'a, I>(tokens: &mut Peekable<I>) -> Result<Expr, Error>
 where
     I: Iterator<Item = &'a Token>,
 {
-    or(tokens)
+    let expr = or(tokens)?;
+
+    if next_type_match(&[Type::Question], tokens).is_some() {
+        let then = ternary(tokens)?;
+
+        if next_type_match(&[Type::Colon], tokens).is_none() {
+            return Err(Error::syntax(
+                "Error: Expected ':' in conditional expression.",
+            ));
+        }
+
+        let otherwise = ternary(tokens)?;
+
+        return Ok(Expr::Ternary(
+            Box::new(expr),
+            Box::new(then),
+            Box::new(otherwise),
+        ));
+    }
+
+    Ok(expr)
+}
+
+fn expression<'a, I>(tokens: &mut Peekable<I>) -> Result<Expr, Error>
+where
+    I: Iterator<Item = &'a Token>,
+{
+    ternary(tokens)
 }
 
-pub fn parse_tokens(tokens: &[Token]) -> Expr {
+pub fn parse_statements(tokens: &[Token]) -> Result<Vec<Expr>, Error> {
     let mut statements = vec![];
     let mut tokens = tokens.iter().peekable();
 
     while tokens.peek().is_some() {
-        statements.push(expression(&mut tokens));
+        statements.push(expression(&mut tokens)?);
     }
 
+    Ok(statements)
+}
+
+pub fn parse_tokens(tokens: &[Token]) -> Result<Expr, Error> {
+    let statements = parse_statements(tokens)?;
+
     if statements.len() == 1 {
-        statements[0].clone()
+        Ok(statements[0].clone())
     } else {
-        Expr::Statements(statements)
+        Ok(Expr::Statements(statements, Locals::default()))
     }
 }
@@ -2,9 +2,11 @@ mod comment;
 mod identifier;
 mod keywords;
 mod number;
+#[allow(clippy::module_inception)]
 mod scanner;
 mod string;
 mod token;
 
-pub(crate) use scanner::scan_tokens;
+pub(crate) use scanner::{scan_tokens, ScanTokens};
+pub use token::Error;
 pub(crate) use token::{Token, Type};
@@ -28,21 +28,92 @@ pub fn scan_tokens(content: &str) -> ScanTokens {
 
     let mut line = 1;
 
+    // Brace depth of each `${ ... }` currently being scanned, innermost last
+    let mut interpolations: Vec<usize> = vec![];
+
     while let Some(char) = chars.next() {
         match char {
             ')' => tokens.push(Token::new(Type::RightParen, ")", None, line)),
             '(' => tokens.push(Token::new(Type::LeftParen, "(", None, line)),
-            '}' => tokens.push(Token::new(Type::RightBrace, "}", None, line)),
-            '{' => tokens.push(Token::new(Type::LeftBrace, "{", None, line)),
-            '*' => tokens.push(Token::new(Type::Star, "*", None, line)),
+            '"' | '}' if char == '"' || interpolations.last() == Some(&0) => {
+                if char == '}' {
+                    interpolations.pop();
+
+                    if tokens
+                        .last()
+                        .is_some_and(|token| token.ty == Type::Interpolation)
+                    {
+                        errors.push(Error {
+                            message: "Expect expression in string interpolation.".to_string(),
+                            line,
+                        });
+                    }
+                }
+
+                match string::scan(char, &mut chars, &mut line) {
+                    Ok((ty, text, value)) => {
+                        if ty == Type::Interpolation {
+                            interpolations.push(0);
+                        }
+
+                        tokens.push(Token::new(ty, text.as_str(), Some(value), line));
+                    }
+                    Err(error) => errors.push(error),
+                }
+            }
+            '}' => {
+                if let Some(depth) = interpolations.last_mut() {
+                    *depth -= 1;
+                }
+
+                tokens.push(Token::new(Type::RightBrace, "}", None, line));
+            }
+            '{' => {
+                if let Some(depth) = interpolations.last_mut() {
+                    *depth += 1;
+                }
+
+                tokens.push(Token::new(Type::LeftBrace, "{", None, line));
+            }
+            '*' => {
+                if next_char_match('*', &mut chars) {
+                    tokens.push(Token::new(Type::StarStar, "**", None, line));
+                } else if next_char_match('=', &mut chars) {
+                    tokens.push(Token::new(Type::StarEqual, "*=", None, line));
+                } else {
+                    tokens.push(Token::new(Type::Star, "*", None, line));
+                }
+            }
+            '%' => tokens.push(Token::new(Type::Percent, "%", None, line)),
+            '\\' => tokens.push(Token::new(Type::Backslash, "\\", None, line)),
             '.' => tokens.push(Token::new(Type::Dot, ".", None, line)),
             ',' => tokens.push(Token::new(Type::Comma, ",", None, line)),
-            '+' => tokens.push(Token::new(Type::Plus, "+", None, line)),
-            '-' => tokens.push(Token::new(Type::Minus, "-", None, line)),
+            '+' => {
+                if next_char_match('+', &mut chars) {
+                    tokens.push(Token::new(Type::PlusPlus, "++", None, line));
+                } else if next_char_match('=', &mut chars) {
+                    tokens.push(Token::new(Type::PlusEqual, "+=", None, line));
+                } else {
+                    tokens.push(Token::new(Type::Plus, "+", None, line));
+                }
+            }
+            '-' => {
+                if next_char_match('-', &mut chars) {
+                    tokens.push(Token::new(Type::MinusMinus, "--", None, line));
+                } else if next_char_match('=', &mut chars) {
+                    tokens.push(Token::new(Type::MinusEqual, "-=", None, line));
+                } else {
+                    tokens.push(Token::new(Type::Minus, "-", None, line));
+                }
+            }
             ';' => tokens.push(Token::new(Type::Semicolon, ";", None, line)),
+            '?' => tokens.push(Token::new(Type::Question, "?", None, line)),
+            ':' => tokens.push(Token::new(Type::Colon, ":", None, line)),
             '/' => {
                 if next_char_match('/', &mut chars) {
                     comment::scan(&mut chars);
+                } else if next_char_match('=', &mut chars) {
+                    tokens.push(Token::new(Type::SlashEqual, "/=", None, line));
                 } else {
                     tokens.push(Token::new(Type::Slash, "/", None, line));
                 }
@@ -75,12 +146,6 @@ pub fn scan_tokens(content: &str) -> ScanTokens {
                     tokens.push(Token::new(Type::Greater, ">", None, line));
                 }
             }
-            '"' => match string::scan(&mut chars, &mut line) {
-                Ok((text, value)) => {
-                    tokens.push(Token::new(Type::String, text.as_str(), Some(value), line));
-                }
-                Err(error) => errors.push(error),
-            },
             '0'..='9' => {
                 let (text, value) = number::scan(char, &mut chars);
                 tokens.push(Token::new(Type::Number, text.as_str(), Some(value), line));
@@ -108,5 +173,12 @@ pub fn scan_tokens(content: &str) -> ScanTokens {
         }
     }
 
+    if !interpolations.is_empty() {
+        errors.push(Error {
+            message: "Unterminated string interpolation.".to_string(),
+            line,
+        });
+    }
+
     ScanTokens { tokens, errors }
 }
@@ -2,30 +2,109 @@ use std::{iter::Peekable, str::Chars};
 
 use super::token;
 
+fn unicode_escape(chars: &mut Peekable<Chars>, text: &mut String) -> Option<char> {
+    chars.next_if_eq(&'{')?;
+    text.push('{');
+
+    let mut hex = String::new();
+
+    while let Some(next) = chars.next_if(|char| *char != '"' && *char != '\n') {
+        text.push(next);
+
+        if next == '}' {
+            break;
+        }
+
+        hex.push(next);
+    }
+
+    if !text.ends_with('}') || hex.is_empty() || hex.len() > 6 {
+        return None;
+    }
+
+    u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32)
+}
+
 pub fn scan(
+    opening: char,
     chars: &mut Peekable<Chars>,
     line: &mut usize,
-) -> Result<(String, String), token::Error> {
+) -> Result<(token::Type, String, String), token::Error> {
+    let mut text = String::new();
     let mut value = String::new();
+    let mut error = None;
+    let mut ty = token::Type::String;
+
+    loop {
+        let Some(next) = chars.next() else {
+            return Err(token::Error {
+                message: "Unterminated string.".to_string(),
+                line: *line,
+            });
+        };
 
-    while let Some(next) = chars.next() {
         if next == '"' {
             break;
         }
 
-        value.push(next);
+        if next == '$' && chars.next_if_eq(&'{').is_some() {
+            ty = token::Type::Interpolation;
+            break;
+        }
+
+        text.push(next);
 
         if next == '\n' {
             *line += 1;
         }
 
-        if chars.peek().is_none() {
-            return Err(token::Error {
-                message: "Unterminated string.".to_string(),
+        if next != '\\' {
+            value.push(next);
+            continue;
+        }
+
+        let Some(escape) = chars.next_if(|char| *char != '\n') else {
+            error.get_or_insert(token::Error {
+                message: "Invalid escape sequence.".to_string(),
                 line: *line,
             });
+            continue;
+        };
+
+        text.push(escape);
+
+        match escape {
+            'n' => value.push('\n'),
+            't' => value.push('\t'),
+            'r' => value.push('\r'),
+            '0' => value.push('\0'),
+            '\\' | '"' | '$' => value.push(escape),
+            'u' => match unicode_escape(chars, &mut text) {
+                Some(char) => value.push(char),
+                None => {
+                    error.get_or_insert(token::Error {
+                        message: "Invalid unicode escape sequence.".to_string(),
+                        line: *line,
+                    });
+                }
+            },
+            _ => {
+                error.get_or_insert(token::Error {
+                    message: format!("Invalid escape sequence: \\{escape}"),
+                    line: *line,
+                });
+            }
         }
     }
 
-    Ok((format!("\"{value}\""), value))
+    if let Some(error) = error {
+        return Err(error);
+    }
+
+    let closing = match ty {
+        token::Type::Interpolation => "${",
+        _ => "\"",
+    };
+
+    Ok((ty, format!("{opening}{text}{closing}"), value))
 }
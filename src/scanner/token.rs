@@ -33,14 +33,25 @@ pub enum Type {
     LeftParen,
     LeftBrace,
     Star,
+    Percent,
+    Backslash,
     Dot,
     Comma,
     Plus,
     Minus,
     Semicolon,
     Slash,
+    Question,
+    Colon,
 
     // One or two character tokens
+    StarStar,
+    PlusPlus,
+    MinusMinus,
+    PlusEqual,
+    MinusEqual,
+    StarEqual,
+    SlashEqual,
     Bang,
     BangEqual,
     Equal,
@@ -52,24 +63,30 @@ pub enum Type {
 
     // Literals
     String,
+    Interpolation,
     Number,
     Identifier,
 
     // Keywords
     And,
+    Catch,
     Class,
     Else,
     False,
+    Finally,
     For,
     Fun,
     If,
+    Import,
     Nil,
     Or,
     Print,
     Return,
     Super,
     This,
+    Throw,
     True,
+    Try,
     Var,
     While,
 }
@@ -82,12 +99,23 @@ impl fmt::Display for Type {
             Type::RightParen => write!(fmt, "RIGHT_PAREN"),
             Type::LeftParen => write!(fmt, "LEFT_PAREN"),
             Type::Star => write!(fmt, "STAR"),
+            Type::Percent => write!(fmt, "PERCENT"),
+            Type::Backslash => write!(fmt, "BACKSLASH"),
             Type::Dot => write!(fmt, "DOT"),
             Type::Comma => write!(fmt, "COMMA"),
             Type::Plus => write!(fmt, "PLUS"),
             Type::Minus => write!(fmt, "MINUS"),
             Type::Semicolon => write!(fmt, "SEMICOLON"),
             Type::Slash => write!(fmt, "SLASH"),
+            Type::Question => write!(fmt, "QUESTION"),
+            Type::Colon => write!(fmt, "COLON"),
+            Type::StarStar => write!(fmt, "STAR_STAR"),
+            Type::PlusPlus => write!(fmt, "PLUS_PLUS"),
+            Type::MinusMinus => write!(fmt, "MINUS_MINUS"),
+            Type::PlusEqual => write!(fmt, "PLUS_EQUAL"),
+            Type::MinusEqual => write!(fmt, "MINUS_EQUAL"),
+            Type::StarEqual => write!(fmt, "STAR_EQUAL"),
+            Type::SlashEqual => write!(fmt, "SLASH_EQUAL"),
             Type::Bang => write!(fmt, "BANG"),
             Type::BangEqual => write!(fmt, "BANG_EQUAL"),
             Type::Equal => write!(fmt, "EQUAL"),
@@ -97,22 +125,28 @@ impl fmt::Display for Type {
             Type::Greater => write!(fmt, "GREATER"),
             Type::GreaterEqual => write!(fmt, "GREATER_EQUAL"),
             Type::String => write!(fmt, "STRING"),
+            Type::Interpolation => write!(fmt, "INTERPOLATION"),
             Type::Number => write!(fmt, "NUMBER"),
             Type::Identifier => write!(fmt, "IDENTIFIER"),
             Type::And => write!(fmt, "AND"),
+            Type::Catch => write!(fmt, "CATCH"),
             Type::Class => write!(fmt, "CLASS"),
             Type::Else => write!(fmt, "ELSE"),
             Type::False => write!(fmt, "FALSE"),
+            Type::Finally => write!(fmt, "FINALLY"),
             Type::For => write!(fmt, "FOR"),
             Type::Fun => write!(fmt, "FUN"),
             Type::If => write!(fmt, "IF"),
+            Type::Import => write!(fmt, "IMPORT"),
             Type::Nil => write!(fmt, "NIL"),
             Type::Or => write!(fmt, "OR"),
             Type::Print => write!(fmt, "PRINT"),
             Type::Return => write!(fmt, "RETURN"),
             Type::Super => write!(fmt, "SUPER"),
             Type::This => write!(fmt, "THIS"),
+            Type::Throw => write!(fmt, "THROW"),
             Type::True => write!(fmt, "TRUE"),
+            Type::Try => write!(fmt, "TRY"),
             Type::Var => write!(fmt, "VAR"),
             Type::While => write!(fmt, "WHILE"),
         }
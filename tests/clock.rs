@@ -0,0 +1,28 @@
+mod common;
+
+use common::output;
+use interpreter_starter_rust::{FakeClock, Interpreter, Options};
+
+#[test]
+fn fake_clock_counts_elapsed_time_from_interpreter_start() {
+    let interpreter = Interpreter::new(Options {
+        fake_clock: Some(FakeClock {
+            start: 1000.0,
+            step: 0.5,
+        }),
+        ..Options::default()
+    });
+
+    assert_eq!(
+        output(
+            &interpreter,
+            "print clock_ms();
+            print clock();
+            print clock_ns();
+            print clock();
+            print clock_ms();"
+        )
+        .unwrap(),
+        "0\n1000\n500000000\n1000.5\n1000\n"
+    );
+}
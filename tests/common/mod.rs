@@ -0,0 +1,38 @@
+use std::{
+    cell::RefCell,
+    io::{self, Write},
+    rc::Rc,
+};
+
+use interpreter_starter_rust::{Error, Interpreter};
+
+// Collects everything written to it, shared with the test that reads it back
+#[derive(Clone, Default)]
+pub struct Capture(pub Rc<RefCell<Vec<u8>>>);
+
+impl Capture {
+    pub fn text(&self) -> String {
+        String::from_utf8(self.0.borrow().clone()).unwrap()
+    }
+}
+
+impl Write for Capture {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// What `source` prints when run by `interpreter`
+#[allow(dead_code)]
+pub fn output(interpreter: &Interpreter, source: &str) -> Result<String, Error> {
+    let capture = Capture::default();
+    interpreter.set_output(capture.clone())?;
+    interpreter.eval_str(source)?;
+
+    Ok(capture.text())
+}
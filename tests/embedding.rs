@@ -0,0 +1,37 @@
+mod common;
+
+use std::io::BufWriter;
+
+use common::Capture;
+use interpreter_starter_rust::{Interpreter, Literal, Value};
+
+#[test]
+fn call_function_flushes_output() {
+    let interpreter = Interpreter::default();
+    let output = Capture::default();
+    interpreter
+        .set_output(BufWriter::new(output.clone()))
+        .unwrap();
+
+    interpreter
+        .eval_str(
+            r#"fun handler(request) {
+                print "got " + request;
+                return request + "!";
+            }"#,
+        )
+        .unwrap();
+
+    for request in ["r0", "r1", "r2"] {
+        let value = interpreter
+            .call_function(
+                "handler",
+                vec![Value::Literal(Literal::String(request.into()))],
+            )
+            .unwrap();
+
+        assert_eq!(value.to_string(), format!("{request}!"));
+    }
+
+    assert_eq!(output.text(), "got r0\ngot r1\ngot r2\n");
+}
@@ -0,0 +1,43 @@
+use interpreter_starter_rust::{Interpreter, Literal, Value};
+
+#[test]
+fn caught_errors_expose_message_and_line() {
+    let interpreter = Interpreter::default();
+
+    let value = interpreter
+        .eval_str(
+            "var caught;
+            try {
+                1 + nil;
+            } catch (e) {
+                caught = e;
+            }
+
+            return error_message(caught) + \" at line \" + str(error_line(caught));",
+        )
+        .unwrap();
+
+    assert_eq!(
+        value.to_string(),
+        "Operands must be two numbers or two strings. at line 3"
+    );
+}
+
+#[test]
+fn caught_errors_are_only_equal_to_themselves() {
+    let interpreter = Interpreter::default();
+
+    let value = interpreter
+        .eval_str(
+            "fun fail() {
+                var f = nil;
+                try { f(); } catch (e) { return e; }
+            }
+
+            var e = fail();
+            return e == e and e != fail() and e != \"Can only call functions and classes.\";",
+        )
+        .unwrap();
+
+    assert!(matches!(value, Value::Literal(Literal::Boolean(true))));
+}
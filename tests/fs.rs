@@ -0,0 +1,63 @@
+#![cfg(unix)]
+
+use std::{env, fs, os::unix::fs::symlink, process};
+
+use interpreter_starter_rust::{Interpreter, Options};
+
+#[test]
+fn dangling_symlink_cannot_escape_the_sandbox() {
+    let dir = env::temp_dir().join(format!("lox-sandbox-{}", process::id()));
+    let root = dir.join("root");
+    let outside = dir.join("outside");
+    fs::create_dir_all(&root).unwrap();
+    fs::create_dir_all(&outside).unwrap();
+    symlink(outside.join("new.txt"), root.join("link")).unwrap();
+
+    let interpreter = Interpreter::new(Options {
+        fs_roots: vec![fs::canonicalize(&root).unwrap()],
+        ..Options::default()
+    });
+
+    let link = root.join("link");
+    let result = interpreter.eval_str(&format!(r#"write_file("{}", "x");"#, link.display()));
+    let escaped = outside.join("new.txt").exists();
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert!(result.is_err());
+    assert!(!escaped);
+}
+
+#[test]
+fn paths_outside_the_sandbox_all_get_the_same_error() {
+    let dir = env::temp_dir().join(format!("lox-probe-{}", process::id()));
+    let root = dir.join("root");
+    fs::create_dir_all(&root).unwrap();
+    fs::write(dir.join("secret.txt"), "x").unwrap();
+
+    let interpreter = Interpreter::new(Options {
+        fs_roots: vec![fs::canonicalize(&root).unwrap()],
+        ..Options::default()
+    });
+    let read = |path: &str| {
+        interpreter
+            .eval_str(&format!(r#"read_file("{path}");"#))
+            .unwrap_err()
+            .to_string()
+    };
+
+    let outside = [
+        "/nonexistent/x".to_string(),
+        dir.join("secret.txt").display().to_string(),
+        dir.join("missing.txt").display().to_string(),
+        dir.join("missing/x").display().to_string(),
+        root.join("missing/../../secret.txt").display().to_string(),
+    ];
+    let inside = read(&root.join("missing/x").display().to_string());
+    fs::remove_dir_all(&dir).unwrap();
+
+    for path in outside {
+        let error = read(&path);
+        assert!(error.contains("is not allowed"), "{path}: {error}");
+    }
+    assert!(inside.contains("Could not resolve"), "{inside}");
+}
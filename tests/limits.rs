@@ -0,0 +1,70 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use interpreter_starter_rust::{Error, Interpreter, Options, RuntimeError};
+
+#[test]
+fn timeout_cannot_be_caught() {
+    let interpreter = Interpreter::new(Options {
+        timeout: Some(Duration::from_millis(100)),
+        ..Options::default()
+    });
+
+    let started = Instant::now();
+    let result = interpreter.eval_str("while (true) { try { while (true) {} } catch (e) {} }");
+
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert!(matches!(
+        result,
+        Err(Error::Runtime(RuntimeError::Limit { ref message, .. })) if message == "Timeout exceeded."
+    ));
+}
+
+#[test]
+fn step_limit_cannot_be_caught() {
+    let interpreter = Interpreter::new(Options {
+        max_steps: Some(1000),
+        ..Options::default()
+    });
+
+    let result = interpreter.eval_str("while (true) { try { while (true) {} } catch (e) {} }");
+
+    assert!(matches!(
+        result,
+        Err(Error::Runtime(RuntimeError::Limit { ref message, .. })) if message == "Step limit exceeded."
+    ));
+}
+
+// The stack `cargo test` and most thread pools give their threads
+const SMALL_STACK: usize = 2 * 1024 * 1024;
+
+#[test]
+fn unbounded_recursion_overflows_cleanly_on_a_small_stack() {
+    let scripts = [
+        "fun rec(n) { return 1 + rec(n + 1); } rec(0);",
+        "fun rec(n) {
+            var a = 1;
+            while (a < 2) {
+                a = a + 1;
+                try { return 1 + (2 * (rec(n + 1) + 0)); } catch (e) { throw e; }
+            }
+        }
+        rec(0);",
+    ];
+
+    for script in scripts {
+        let result = thread::Builder::new()
+            .stack_size(SMALL_STACK)
+            .spawn(move || match Interpreter::default().eval_str(script) {
+                Err(Error::Runtime(RuntimeError::Runtime { message, .. })) => message,
+                result => format!("{result:?}"),
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+
+        assert_eq!(result, "Stack overflow.");
+    }
+}
@@ -0,0 +1,43 @@
+use interpreter_starter_rust::{CountingAllocator, Error, Interpreter, Options, RuntimeError};
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn out_of_memory(source: &str) -> bool {
+    let interpreter = Interpreter::new(Options {
+        max_memory: Some(8_000_000),
+        ..Options::default()
+    });
+
+    matches!(
+        interpreter.eval_str(source),
+        Err(Error::Runtime(RuntimeError::Limit { ref message, .. })) if message == "Out of memory."
+    )
+}
+
+#[test]
+fn natives_reserve_before_allocating() {
+    let quarter_megabyte = r#"
+        var s = "a";
+        for (var i = 0; i < 18; i = i + 1) { s = s + s; }
+    "#;
+
+    assert!(out_of_memory(&format!(
+        r#"{quarter_megabyte} replace(s, "a", s);"#
+    )));
+    assert!(out_of_memory(&format!(
+        r#"{quarter_megabyte} join(split(substr(s, 0, 100), ""), s);"#
+    )));
+}
+
+#[test]
+fn json_stringify_counts_escapes() {
+    // Each control character is written as a six byte `\u0001` escape
+    assert!(out_of_memory(
+        r#"
+        var s = chr(1);
+        for (var i = 0; i < 21; i = i + 1) { s = s + s; }
+        json_stringify(s, nil);
+        "#
+    ));
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use common::output;
+use interpreter_starter_rust::{Interpreter, OptLevel, Options};
+
+fn run(source: &str, opt_level: OptLevel) -> Result<String, String> {
+    let interpreter = Interpreter::new(Options {
+        opt_level,
+        ..Options::default()
+    });
+
+    output(&interpreter, source).map_err(|error| error.to_string())
+}
+
+// Runs `source` unoptimized and optimized, which must be indistinguishable
+fn both(source: &str) -> Result<String, String> {
+    let unoptimized = run(source, OptLevel::O0);
+    assert_eq!(unoptimized, run(source, OptLevel::O1));
+
+    unoptimized
+}
+
+#[test]
+fn folding() {
+    assert_eq!(
+        both(
+            "print 1 + 2 * 3;
+            print -(4 - 6);
+            print !nil;
+            print \"a\" + \"b\" + \"c\";
+            print \"${1 + 1} and ${\"x\"}\";
+            print 1 / 3;
+            print (1 < 2) == !false;"
+        ),
+        Ok("7\n2\ntrue\nabc\n2 and x\n0.3333333333333333\ntrue\n".to_string())
+    );
+}
+
+#[test]
+fn folding_leaves_errors_for_runtime() {
+    let error = both("print \"before\";\nprint 1 + \"a\";").unwrap_err();
+    assert!(error.contains("[line 2]"), "{error}");
+
+    assert!(both("print -\"a\";").is_err());
+}
+
+#[test]
+fn short_circuit() {
+    assert_eq!(
+        both(
+            "fun said(value) { print value; return value; }
+            print false and said(\"and false\");
+            print true and said(\"and true\");
+            print nil or said(\"or nil\");
+            print 1 or said(\"or 1\");
+            print true ? said(\"then\") : said(\"else\");
+            print nil ? said(\"then\") : said(\"else\");"
+        ),
+        Ok("false\nand true\nand true\nor nil\nor nil\n1\nthen\nthen\nelse\nelse\n".to_string())
+    );
+}
+
+#[test]
+fn dead_branches_and_loops() {
+    assert_eq!(
+        both(
+            "fun said(value) { print value; return value; }
+            if (false) said(\"if false\");
+            if (nil) said(\"if nil\"); else said(\"else nil\");
+            if (true) said(\"if true\"); else said(\"else true\");
+            while (false) said(\"while false\");
+            for (var i = said(\"init\"); false; i = i + 1) { said(\"body\"); }
+            print \"done\";"
+        ),
+        Ok("else nil\nif true\ninit\ndone\n".to_string())
+    );
+}
+
+#[test]
+fn code_after_return() {
+    assert_eq!(
+        both(
+            "fun f() {
+                print \"before\";
+                return \"returned\";
+                print \"after\";
+                undefined();
+            }
+            print f();
+
+            fun g() {
+                {
+                    return \"inner\";
+                    print \"after inner\";
+                }
+                print \"after block\";
+            }
+            print g();"
+        ),
+        Ok("before\nreturned\ninner\n".to_string())
+    );
+}
+
+#[test]
+fn code_after_throw() {
+    assert_eq!(
+        both(
+            "try {
+                print \"before\";
+                throw \"thrown\";
+                print \"after\";
+            } catch (error) {
+                print error;
+            }"
+        ),
+        Ok("before\nthrown\n".to_string())
+    );
+}
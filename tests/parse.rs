@@ -0,0 +1,19 @@
+use interpreter_starter_rust::{Error, Interpreter};
+
+#[test]
+fn truncated_source_is_a_parse_error() {
+    let interpreter = Interpreter::default();
+
+    for source in [
+        "for (",
+        "for (;",
+        "for (;;",
+        "for (;;)",
+        "for (var i = 0; i < 1;",
+    ] {
+        assert!(
+            matches!(interpreter.eval_str(source), Err(Error::Parse(_))),
+            "{source}"
+        );
+    }
+}
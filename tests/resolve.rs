@@ -0,0 +1,167 @@
+mod common;
+
+use std::{env, fs, process};
+
+use common::{output, Capture};
+use interpreter_starter_rust::Interpreter;
+
+fn run(source: &str) -> String {
+    output(&Interpreter::default(), source).unwrap()
+}
+
+#[test]
+fn shadowing() {
+    assert_eq!(
+        run("var a = \"global\";
+            {
+                var a = \"outer\";
+                {
+                    var a = \"inner\";
+                    print a;
+                }
+                print a;
+            }
+            print a;
+
+            fun f(a) {
+                { var a = a + \"!\"; print a; }
+                print a;
+            }
+            f(\"arg\");"),
+        "inner\nouter\nglobal\narg!\narg\n"
+    );
+}
+
+#[test]
+fn closures_capture_block_locals() {
+    assert_eq!(
+        run("fun counter() {
+                var count = 0;
+                fun increment() {
+                    count = count + 1;
+                    return count;
+                }
+                return increment;
+            }
+
+            var first = counter();
+            var second = counter();
+            print first();
+            print first();
+            print second();
+
+            var show;
+            {
+                var message = \"block\";
+                fun f() { print message; }
+                show = f;
+            }
+            show();"),
+        "1\n2\n1\nblock\n"
+    );
+}
+
+#[test]
+fn closures_capture_loop_locals() {
+    assert_eq!(
+        run("var first;
+            var last;
+            for (var i = 0; i < 3; i = i + 1) {
+                var j = i * 10;
+                fun f() { return j; }
+                if (i == 0) first = f;
+                last = f;
+            }
+            print first();
+            print last();
+
+            var k = 0;
+            var g;
+            while (k < 2) {
+                var seen = k;
+                fun h() { return seen; }
+                g = h;
+                k = k + 1;
+            }
+            print g();"),
+        "0\n20\n1\n"
+    );
+}
+
+#[test]
+fn catch_variables() {
+    assert_eq!(
+        run("var e = \"outer\";
+            fun fail() { throw \"thrown\"; }
+
+            try {
+                fail();
+            } catch (e) {
+                fun f() { return e; }
+                print f();
+                var inner = e + \"!\";
+                print inner;
+            }
+            print e;
+
+            try { throw 1; } catch (e) { try { throw 2; } catch (e) { print e; } print e; }"),
+        "thrown\nthrown!\nouter\n2\n1\n"
+    );
+}
+
+// As before slots were resolved, a closure sees a local of an enclosing block
+// once its declaration has run, and the global of that name until then
+#[test]
+fn locals_declared_after_use() {
+    assert_eq!(
+        run("var a = \"global\";
+            {
+                fun show() { print a; }
+                show();
+                var a = \"local\";
+                show();
+                print a;
+            }
+
+            fun early() { return later(); }
+            fun later() { return \"later\"; }
+            print early();"),
+        "global\nlocal\nlocal\nlater\n"
+    );
+}
+
+#[test]
+fn imports_inside_functions() {
+    let dir = env::temp_dir().join(format!("lox-resolve-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("greet.lox"),
+        "fun greet(name) { return \"hello \" + name; }\nvar punctuation = \"!\";",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("main.lox"),
+        "fun run() {
+            import { greet, punctuation } from \"greet.lox\";
+            print greet(\"lox\") + punctuation;
+        }
+        run();
+
+        fun all() {
+            var punctuation = \"?\";
+            import \"greet.lox\";
+            print greet(\"again\") + punctuation;
+        }
+        all();",
+    )
+    .unwrap();
+
+    let interpreter = Interpreter::default();
+    let capture = Capture::default();
+    interpreter.set_output(capture.clone()).unwrap();
+    let result = interpreter.run_file(dir.join("main.lox"));
+    fs::remove_dir_all(&dir).unwrap();
+
+    result.unwrap();
+    assert_eq!(capture.text(), "hello lox!\nhello again!\n");
+}
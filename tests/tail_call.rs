@@ -0,0 +1,106 @@
+use interpreter_starter_rust::{Interpreter, Literal, Options, Value};
+
+fn number(value: &Value) -> f64 {
+    match value {
+        Value::Literal(Literal::Number(number)) => *number,
+        value => panic!("expected a number, got {value}"),
+    }
+}
+
+#[test]
+fn self_recursion_runs_a_million_times() {
+    let interpreter = Interpreter::default();
+
+    let value = interpreter
+        .eval_str(
+            "fun loop(n, acc) {
+                if (n == 0) return acc;
+                return loop(n - 1, acc + 1);
+            }
+
+            return loop(1000000, 0);",
+        )
+        .unwrap();
+
+    assert_eq!(number(&value), 1_000_000.0);
+}
+
+#[test]
+fn mutual_recursion_runs_a_million_times() {
+    let interpreter = Interpreter::default();
+
+    let value = interpreter
+        .eval_str(
+            "fun even(n) {
+                if (n == 0) return true;
+                return odd(n - 1);
+            }
+
+            fun odd(n) {
+                if (n == 0) return false;
+                return even(n - 1);
+            }
+
+            return even(1000000);",
+        )
+        .unwrap();
+
+    assert!(matches!(value, Value::Literal(Literal::Boolean(true))));
+}
+
+#[test]
+fn stack_traces_mark_elided_frames() {
+    let interpreter = Interpreter::default();
+
+    let error = interpreter
+        .eval_str(
+            "fun inner() {
+                return nil + 1;
+            }
+
+            fun outer() {
+                return inner();
+            }
+
+            outer();",
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "Operands must be two numbers or two strings.
+[line 2] in inner()
+[line 6] ... tail calls ...
+[line 9] in script"
+    );
+}
+
+#[test]
+fn step_limit_in_a_tail_call_keeps_the_caller() {
+    let interpreter = Interpreter::new(Options {
+        max_steps: Some(100),
+        ..Options::default()
+    });
+
+    let error = interpreter
+        .eval_str(
+            "fun loop(n) {
+                return loop(n + 1);
+            }
+
+            fun start() {
+                return 1 + loop(0);
+            }
+
+            start();",
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "Step limit exceeded.
+[line 2] ... tail calls ...
+[line 6] in start()
+[line 9] in script"
+    );
+}